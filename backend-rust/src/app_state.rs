@@ -1,13 +1,30 @@
+use std::sync::Arc;
+
 use crate::config::AppConfig;
 use crate::db::Database;
-use crate::embedding::OpenAIEmbeddingService;
-use crate::services::JobManager;
+use crate::embedding::EmbeddingProvider;
+use crate::github::cache::ResourceCache;
+use crate::github::ratelimit::RateLimitPolicy;
+use crate::services::{JobManager, Notifier};
+use crate::session::SessionManager;
 
 /// Shared application state containing all services
 #[derive(Clone)]
 pub struct AppState {
     pub database: Database,
-    pub embedding_service: OpenAIEmbeddingService,
+    pub embedding_service: Arc<dyn EmbeddingProvider>,
     pub config: AppConfig,
     pub job_manager: JobManager,
+    /// Sink for terminal job notifications; future sinks (email, Discord) can be
+    /// swapped in without touching the job manager.
+    pub notifier: Arc<dyn Notifier>,
+    /// Signs and verifies the stateless session tokens backing the authenticated
+    /// extractors.
+    pub session_manager: SessionManager,
+    /// Conditional-request cache attached to every GitHub client, or `None` when
+    /// caching is disabled in configuration.
+    pub github_cache: Option<ResourceCache>,
+    /// Rate-limit backoff policy applied to every GitHub client, built from
+    /// configuration.
+    pub github_rate_limit_policy: RateLimitPolicy,
 }
@@ -1,10 +1,47 @@
-use anyhow::Result;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
 use chrono::Utc;
 use pgvector::Vector;
-use sqlx::{PgPool, Row, types::Decimal};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{ConnectOptions, PgPool, Row, types::Decimal};
+use uuid::Uuid;
 
 use crate::types::repository::Repository;
 
+/// How a [`Database`] should obtain its connection pool.
+///
+/// `Existing` wraps a pool the caller already built and tuned, while `Fresh`
+/// centralizes pool construction here so isolated pools (e.g. per-test) can be
+/// spun up with quiet statement logging and independent sizing.
+#[derive(Debug, Clone)]
+pub enum ConnectionOptions {
+    /// Wrap an already-constructed pool, as `Database::new` does.
+    Existing(PgPool),
+    /// Build a new pool from a connection URL.
+    Fresh {
+        /// Postgres connection URL.
+        url: String,
+        /// Maximum number of pooled connections.
+        max_connections: u32,
+        /// Suppress sqlx's per-statement query logging, so embedding-heavy SQL
+        /// does not flood the logs.
+        disable_statement_logging: bool,
+    },
+}
+
+/// Aggregate ingestion-retry health for a single job.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetryStats {
+    /// Total number of retry attempts made across repositories still pending.
+    pub retried: i64,
+    /// Repositories awaiting another retry.
+    pub pending_retry: i64,
+    /// Repositories that exhausted their retries or hit a non-retryable error.
+    pub permanently_failed: i64,
+}
+
 /// Database abstraction layer that encapsulates all database operations.
 /// This provides a high-level interface for all database interactions,
 /// centralizing SQL queries and making testing easier.
@@ -19,6 +56,38 @@ impl Database {
         Self { pool }
     }
 
+    /// Construct a `Database` from [`ConnectionOptions`].
+    ///
+    /// `Existing` wraps the caller's pool unchanged; `Fresh` builds a pool via
+    /// [`PgPoolOptions`], optionally disabling statement logging so
+    /// embedding-heavy SQL stays out of the logs.
+    pub async fn connect(opts: ConnectionOptions) -> Result<Self> {
+        match opts {
+            ConnectionOptions::Existing(pool) => Ok(Self::new(pool)),
+            ConnectionOptions::Fresh {
+                url,
+                max_connections,
+                disable_statement_logging,
+            } => {
+                let mut connect_options = PgConnectOptions::from_str(&url)
+                    .with_context(|| format!("Invalid database URL: {url}"))?;
+                if disable_statement_logging {
+                    connect_options = connect_options.disable_statement_logging();
+                }
+
+                let pool = PgPoolOptions::new()
+                    .max_connections(max_connections)
+                    .acquire_timeout(Duration::from_secs(30))
+                    .connect_with(connect_options)
+                    .await
+                    .with_context(|| format!("Failed to connect to database at {url}"))?;
+
+                tracing::info!("Database connection pool initialized");
+                Ok(Self::new(pool))
+            }
+        }
+    }
+
     /// Get a reference to the underlying connection pool
     /// This should only be used in tests or special cases
     pub fn pool(&self) -> &PgPool {
@@ -125,6 +194,130 @@ impl Database {
         Ok(results)
     }
 
+    /// Perform a single page of semantic search on all repositories.
+    ///
+    /// Results are ordered by `(similarity_score DESC, id ASC)`. When `after` is
+    /// `Some((score, id))` the scan resumes strictly past that boundary, so equal
+    /// similarity scores page deterministically via the tie-breaker id.
+    pub async fn semantic_search_repositories_page(
+        &self,
+        query_embedding: &[f32],
+        page_size: usize,
+        after: Option<(f64, Decimal)>,
+    ) -> Result<Vec<(Repository, f64)>, sqlx::Error> {
+        let query_vector = Vector::from(query_embedding.to_vec());
+        let (after_score, after_id) = match after {
+            Some((score, id)) => (Some(score), Some(id)),
+            None => (None, None),
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                id, name, owner, description, readme_content, topics,
+                homepage_url, created_at, last_updated,
+                1 - (embedding <=> $1) AS similarity_score
+            FROM repositories
+            WHERE $3::float8 IS NULL
+               OR (1 - (embedding <=> $1)) < $3
+               OR ((1 - (embedding <=> $1)) = $3 AND id > $4)
+            ORDER BY embedding <=> $1, id
+            LIMIT $2
+            "#,
+        )
+        .bind(query_vector)
+        .bind(page_size as i64)
+        .bind(after_score)
+        .bind(after_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Self::rows_into_results_full(rows))
+    }
+
+    /// Perform a single page of semantic search on a user's starred repositories.
+    ///
+    /// Uses the same `(similarity_score DESC, id ASC)` keyset ordering as
+    /// [`Self::semantic_search_repositories_page`].
+    pub async fn semantic_search_starred_repositories_page(
+        &self,
+        query_embedding: &[f32],
+        user_id: Decimal,
+        page_size: usize,
+        after: Option<(f64, Decimal)>,
+    ) -> Result<Vec<(Repository, f64)>, sqlx::Error> {
+        let query_vector = Vector::from(query_embedding.to_vec());
+        let (after_score, after_id) = match after {
+            Some((score, id)) => (Some(score), Some(id)),
+            None => (None, None),
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                r.id, r.name, r.owner, r.description, r.readme_content, r.topics,
+                r.homepage_url, r.created_at, r.last_updated,
+                1 - (r.embedding <=> $1) AS similarity_score
+            FROM repositories r
+            JOIN user_stars us ON us.user_id = $2 AND r.id = ANY(us.repo_ids)
+            WHERE $4::float8 IS NULL
+               OR (1 - (r.embedding <=> $1)) < $4
+               OR ((1 - (r.embedding <=> $1)) = $4 AND r.id > $5)
+            ORDER BY r.embedding <=> $1, r.id
+            LIMIT $3
+            "#,
+        )
+        .bind(query_vector)
+        .bind(user_id)
+        .bind(page_size as i64)
+        .bind(after_score)
+        .bind(after_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Self::rows_into_results_full(rows))
+    }
+
+    /// Map search result rows (carrying a `similarity_score` column) into repositories.
+    fn rows_into_results(rows: Vec<sqlx::postgres::PgRow>) -> Vec<(Repository, f32)> {
+        rows.iter().map(Self::row_into_result).collect()
+    }
+
+    /// Map search result rows, preserving the full `f64` similarity so keyset
+    /// pagination can resume on the exact boundary value the database computed.
+    fn rows_into_results_full(rows: Vec<sqlx::postgres::PgRow>) -> Vec<(Repository, f64)> {
+        rows.iter()
+            .map(|row| {
+                let similarity_score: f64 = row.get("similarity_score");
+                (Self::row_into_repo(row), similarity_score)
+            })
+            .collect()
+    }
+
+    /// Map a single search result row into a `(Repository, similarity)` pair.
+    fn row_into_result(row: &sqlx::postgres::PgRow) -> (Repository, f32) {
+        let similarity_score: f64 = row.get("similarity_score");
+        (Self::row_into_repo(row), similarity_score as f32)
+    }
+
+    /// Build a `Repository` from a search result row.
+    fn row_into_repo(row: &sqlx::postgres::PgRow) -> Repository {
+        Repository {
+            id: row.get("id"),
+            name: row.get("name"),
+            owner: row.get("owner"),
+            description: row.get("description"),
+            readme_content: row.get("readme_content"),
+            topics: row
+                .get::<Option<Vec<String>>, _>("topics")
+                .unwrap_or_default(),
+            homepage_url: row.get("homepage_url"),
+            embedding: None,
+            created_at: row.get("created_at"),
+            last_updated: row.get("last_updated"),
+        }
+    }
+
     /// Perform semantic search on repositories starred by a specific user
     pub async fn semantic_search_starred_repositories(
         &self,
@@ -176,6 +369,289 @@ impl Database {
         Ok(results)
     }
 
+    /// Stream semantic-search results over all repositories without buffering the
+    /// full result set.
+    ///
+    /// Rows are yielded lazily as Postgres produces them, so callers can forward
+    /// results downstream or apply an early cutoff without materializing all
+    /// `top_k` rows first.
+    pub fn semantic_search_repositories_stream(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+    ) -> impl futures::Stream<Item = Result<(Repository, f32), sqlx::Error>> + '_ {
+        use futures::StreamExt;
+
+        let query_vector = Vector::from(query_embedding.to_vec());
+        sqlx::query(
+            r#"
+            SELECT
+                id, name, owner, description, readme_content, topics,
+                homepage_url, created_at, last_updated,
+                1 - (embedding <=> $1) AS similarity_score
+            FROM repositories
+            ORDER BY embedding <=> $1
+            LIMIT $2
+            "#,
+        )
+        .bind(query_vector)
+        .bind(top_k as i64)
+        .fetch(&self.pool)
+        .map(|row| row.map(|row| Self::row_into_result(&row)))
+    }
+
+    /// Streaming counterpart of [`Self::semantic_search_starred_repositories`].
+    pub fn semantic_search_starred_repositories_stream(
+        &self,
+        query_embedding: &[f32],
+        user_id: Decimal,
+        top_k: usize,
+    ) -> impl futures::Stream<Item = Result<(Repository, f32), sqlx::Error>> + '_ {
+        use futures::StreamExt;
+
+        let query_vector = Vector::from(query_embedding.to_vec());
+        sqlx::query(
+            r#"
+            SELECT
+                r.id, r.name, r.owner, r.description, r.readme_content, r.topics,
+                r.homepage_url, r.created_at, r.last_updated,
+                1 - (r.embedding <=> $1) AS similarity_score
+            FROM repositories r
+            JOIN user_stars us ON us.user_id = $2 AND r.id = ANY(us.repo_ids)
+            ORDER BY r.embedding <=> $1
+            LIMIT $3
+            "#,
+        )
+        .bind(query_vector)
+        .bind(user_id)
+        .bind(top_k as i64)
+        .fetch(&self.pool)
+        .map(|row| row.map(|row| Self::row_into_result(&row)))
+    }
+
+    /// Hybrid lexical + vector search fusing a cosine-ranked list and a
+    /// full-text-ranked list with Reciprocal Rank Fusion.
+    ///
+    /// Each subquery is run independently (ordered by `embedding <=> $1` and by
+    /// `ts_rank_cd` over `search_vec` respectively) and capped at `top_k * 4`
+    /// candidates. A repository's fused score is `Σ 1/(k + rank_i)` across the
+    /// lists it appears in, with `k = 60` and ranks starting at 1. RRF needs no
+    /// score normalization between the two incomparable metrics. Repositories
+    /// that surface in only one list (the common case for exact keyword hits)
+    /// still get a score, while those found in both lists rank higher.
+    pub async fn hybrid_search_repositories(
+        &self,
+        query_text: &str,
+        query_embedding: &[f32],
+        top_k: usize,
+    ) -> Result<Vec<(Repository, f32)>, sqlx::Error> {
+        use std::collections::HashMap;
+
+        const RRF_K: f32 = 60.0;
+        let candidate_limit = (top_k * 4) as i64;
+        let query_vector = Vector::from(query_embedding.to_vec());
+
+        let vector_rows = sqlx::query(
+            r#"
+            SELECT
+                id, name, owner, description, readme_content, topics,
+                homepage_url, created_at, last_updated,
+                1 - (embedding <=> $1) AS similarity_score
+            FROM repositories
+            ORDER BY embedding <=> $1
+            LIMIT $2
+            "#,
+        )
+        .bind(&query_vector)
+        .bind(candidate_limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let lexical_rows = sqlx::query(
+            r#"
+            SELECT
+                id, name, owner, description, readme_content, topics,
+                homepage_url, created_at, last_updated,
+                ts_rank_cd(search_vec, websearch_to_tsquery('english', $1))::float8 AS similarity_score
+            FROM repositories
+            WHERE search_vec @@ websearch_to_tsquery('english', $1)
+            ORDER BY similarity_score DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(query_text)
+        .bind(candidate_limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Fuse both ranked lists by repository id. `fused` accumulates the RRF
+        // score; `repos` keeps the first-seen `Repository` for each id.
+        let mut fused: HashMap<Decimal, f32> = HashMap::new();
+        let mut repos: HashMap<Decimal, Repository> = HashMap::new();
+        for rows in [&vector_rows, &lexical_rows] {
+            for (rank, row) in rows.iter().enumerate() {
+                let (repo, _) = Self::row_into_result(row);
+                let id = repo.id;
+                *fused.entry(id).or_insert(0.0) += 1.0 / (RRF_K + (rank as f32 + 1.0));
+                repos.entry(id).or_insert(repo);
+            }
+        }
+
+        let mut results: Vec<(Repository, f32)> = fused
+            .into_iter()
+            .filter_map(|(id, score)| repos.remove(&id).map(|repo| (repo, score)))
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        results.truncate(top_k);
+        Ok(results)
+    }
+
+    // ===== Saved Query Operations =====
+
+    /// Persist a saved semantic query for `user_id` and return the created row.
+    pub async fn create_saved_query(
+        &self,
+        user_id: Decimal,
+        query_embedding: &[f32],
+        label: &str,
+    ) -> Result<crate::types::SavedQuery, sqlx::Error> {
+        let vector = Vector::from(query_embedding.to_vec());
+        let row = sqlx::query(
+            r#"
+            INSERT INTO saved_queries (user_id, query_embedding, label)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, label, created_at, last_seen
+            "#,
+        )
+        .bind(user_id)
+        .bind(vector)
+        .bind(label)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(Self::row_into_saved_query(&row))
+    }
+
+    /// List every saved query belonging to `user_id`, newest first.
+    pub async fn list_saved_queries(
+        &self,
+        user_id: Decimal,
+    ) -> Result<Vec<crate::types::SavedQuery>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, label, created_at, last_seen
+            FROM saved_queries
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::row_into_saved_query).collect())
+    }
+
+    /// Fetch a single saved query together with its embedding, used when
+    /// re-running the search to render a feed.
+    pub async fn get_saved_query_embedding(
+        &self,
+        saved_query_id: i32,
+    ) -> Result<Option<(crate::types::SavedQuery, Vec<f32>)>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, label, created_at, last_seen, query_embedding
+            FROM saved_queries
+            WHERE id = $1
+            "#,
+        )
+        .bind(saved_query_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| {
+            let embedding = row.get::<Vector, _>("query_embedding").to_vec();
+            (Self::row_into_saved_query(&row), embedding)
+        }))
+    }
+
+    /// Advance a saved query's `last_seen` watermark, so the next feed render
+    /// only reports repositories matched after this point.
+    pub async fn touch_saved_query(
+        &self,
+        saved_query_id: i32,
+        last_seen: chrono::DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE saved_queries SET last_seen = $1 WHERE id = $2")
+            .bind(last_seen)
+            .bind(saved_query_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a saved query, scoped to its owner so users cannot remove another
+    /// user's subscription.
+    pub async fn delete_saved_query(
+        &self,
+        saved_query_id: i32,
+        user_id: Decimal,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM saved_queries WHERE id = $1 AND user_id = $2")
+            .bind(saved_query_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Find repositories that have appeared since `since` and match the saved
+    /// query's embedding at or above `threshold` cosine similarity.
+    ///
+    /// This is the query behind each discovery feed: it runs the same
+    /// `embedding <=> $1` search as [`Self::semantic_search_repositories`] but
+    /// restricts to freshly-created repositories and drops weak matches, so a
+    /// feed reader only surfaces genuinely new, relevant results.
+    pub async fn new_matches_since(
+        &self,
+        query_embedding: &[f32],
+        since: chrono::DateTime<Utc>,
+        threshold: f32,
+        top_k: usize,
+    ) -> Result<Vec<(Repository, f32)>, sqlx::Error> {
+        let query_vector = Vector::from(query_embedding.to_vec());
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                id, name, owner, description, readme_content, topics,
+                homepage_url, created_at, last_updated,
+                1 - (embedding <=> $1) AS similarity_score
+            FROM repositories
+            WHERE created_at > $2
+              AND 1 - (embedding <=> $1) >= $3
+            ORDER BY embedding <=> $1
+            LIMIT $4
+            "#,
+        )
+        .bind(query_vector)
+        .bind(since)
+        .bind(threshold as f64)
+        .bind(top_k as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Self::rows_into_results(rows))
+    }
+
+    /// Map a saved-query row into a [`SavedQuery`].
+    fn row_into_saved_query(row: &sqlx::postgres::PgRow) -> crate::types::SavedQuery {
+        crate::types::SavedQuery {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            label: row.get("label"),
+            created_at: row.get("created_at"),
+            last_seen: row.get("last_seen"),
+        }
+    }
+
     // ===== User Stars Operations =====
 
     /// Update or insert user stars entry with repository IDs
@@ -207,6 +683,106 @@ impl Database {
         Ok(())
     }
 
+    /// Persist (or refresh) the OAuth cache record for a user, as written after
+    /// either the browser or device authorization flow completes.
+    pub async fn upsert_oauth_cache(
+        &self,
+        oauth: &crate::types::OAuthCacheObject,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_cache (user_id, github_username, following_count, account_created_at, updated_at)
+            VALUES ($1, $2, $3, $4, now())
+            ON CONFLICT (user_id)
+            DO UPDATE SET
+                github_username = EXCLUDED.github_username,
+                following_count = EXCLUDED.following_count,
+                account_created_at = EXCLUDED.account_created_at,
+                updated_at = now()
+            "#,
+        )
+        .bind(&oauth.user_id)
+        .bind(&oauth.github_username)
+        .bind(oauth.following_count)
+        .bind(oauth.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Store the per-user GitHub access token used to rehydrate a
+    /// [`crate::github::GitHubClient`] when a session token is validated.
+    pub async fn set_oauth_access_token(
+        &self,
+        user_id: &str,
+        access_token: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE oauth_cache SET github_access_token = $2, updated_at = now() WHERE user_id = $1")
+            .bind(user_id)
+            .bind(access_token)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Fetch the stored GitHub access token for a user, if one is on file.
+    pub async fn get_oauth_access_token(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let row = sqlx::query("SELECT github_access_token FROM oauth_cache WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.and_then(|r| r.get::<Option<String>, _>("github_access_token")))
+    }
+
+    /// Load the cached OAuth record for a user, as consumed by the eligibility
+    /// gate when authenticating a request.
+    pub async fn get_oauth_cache(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<crate::types::OAuthCacheObject>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT user_id, github_username, following_count, account_created_at
+            FROM oauth_cache
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|r| {
+            crate::types::OAuthCacheObject::new(
+                r.get("user_id"),
+                r.get("account_created_at"),
+                r.get("following_count"),
+                r.get("github_username"),
+            )
+        }))
+    }
+
+    /// Add a token's jti to the revocation set, invalidating it before expiry.
+    pub async fn revoke_session(&self, jti: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO revoked_sessions (jti, revoked_at) VALUES ($1, now()) ON CONFLICT (jti) DO NOTHING",
+        )
+        .bind(jti)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether a token's jti has been revoked.
+    pub async fn is_session_revoked(&self, jti: &str) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query("SELECT EXISTS (SELECT 1 FROM revoked_sessions WHERE jti = $1)")
+            .bind(jti)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.get::<bool, _>(0))
+    }
+
     /// Check if a user has any starred repositories stored
     pub async fn user_has_stars(&self, user_id: Decimal) -> Result<bool, sqlx::Error> {
         let row = sqlx::query("SELECT COUNT(*) as count FROM user_stars WHERE user_id = $1")
@@ -261,8 +837,8 @@ impl Database {
             r#"
             INSERT INTO user_jobs (user_id, status, total_repos, processed_repos, failed_repos)
             VALUES ($1, 'pending', 0, 0, 0)
-            RETURNING id, user_id, status, total_repos, processed_repos, failed_repos, 
-                      created_at, updated_at, completed_at
+            RETURNING id, user_id, status::text AS status, total_repos, processed_repos, failed_repos,
+                      worker_id, heartbeat, created_at, updated_at, completed_at
             "#,
         )
         .bind(user_id)
@@ -275,8 +851,8 @@ impl Database {
     pub async fn get_job(&self, job_id: i32) -> Result<Option<crate::types::UserJob>, sqlx::Error> {
         let job = sqlx::query_as::<_, crate::types::UserJob>(
             r#"
-            SELECT id, user_id, status, total_repos, processed_repos, failed_repos, 
-                   created_at, updated_at, completed_at
+            SELECT id, user_id, status::text AS status, total_repos, processed_repos, failed_repos,
+                   worker_id, heartbeat, created_at, updated_at, completed_at
             FROM user_jobs
             WHERE id = $1
             "#,
@@ -294,8 +870,8 @@ impl Database {
     ) -> Result<Option<crate::types::UserJob>, sqlx::Error> {
         let job = sqlx::query_as::<_, crate::types::UserJob>(
             r#"
-            SELECT id, user_id, status, total_repos, processed_repos, failed_repos, 
-                   created_at, updated_at, completed_at
+            SELECT id, user_id, status::text AS status, total_repos, processed_repos, failed_repos,
+                   worker_id, heartbeat, created_at, updated_at, completed_at
             FROM user_jobs
             WHERE user_id = $1
             ORDER BY created_at DESC
@@ -309,11 +885,14 @@ impl Database {
     }
 
     /// Update a job's status
+    ///
+    /// `status` is bound as text and cast to the `job_status` enum; Postgres
+    /// does not implicitly cast text to an enum on assignment.
     pub async fn update_job_status(&self, job_id: i32, status: &str) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            UPDATE user_jobs 
-            SET status = $1, updated_at = CURRENT_TIMESTAMP
+            UPDATE user_jobs
+            SET status = $1::job_status, updated_at = CURRENT_TIMESTAMP
             WHERE id = $2
             "#,
         )
@@ -324,6 +903,26 @@ impl Database {
         Ok(())
     }
 
+    /// Update a job's human-readable progress message.
+    pub async fn update_job_progress_message(
+        &self,
+        job_id: i32,
+        message: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE user_jobs
+            SET progress_message = $1, updated_at = CURRENT_TIMESTAMP
+            WHERE id = $2
+            "#,
+        )
+        .bind(message)
+        .bind(job_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     /// Update a job's progress
     pub async fn update_job_progress(
         &self,
@@ -334,9 +933,14 @@ impl Database {
     ) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            UPDATE user_jobs 
-            SET total_repos = $1, processed_repos = $2, failed_repos = $3, updated_at = CURRENT_TIMESTAMP
-            WHERE id = $4
+            WITH updated AS (
+                UPDATE user_jobs
+                SET total_repos = $1, processed_repos = $2, failed_repos = $3, updated_at = now()
+                WHERE id = $4
+                RETURNING id, user_id, status::text AS status, total_repos, processed_repos,
+                          failed_repos, worker_id, heartbeat, created_at, updated_at, completed_at
+            )
+            SELECT pg_notify('job_updates', row_to_json(updated)::text) FROM updated
             "#,
         )
         .bind(total_repos)
@@ -352,9 +956,14 @@ impl Database {
     pub async fn complete_job(&self, job_id: i32) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            UPDATE user_jobs 
-            SET status = 'completed', completed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
-            WHERE id = $1
+            WITH updated AS (
+                UPDATE user_jobs
+                SET status = 'completed', completed_at = now(), updated_at = now()
+                WHERE id = $1
+                RETURNING id, user_id, status::text AS status, total_repos, processed_repos,
+                          failed_repos, worker_id, heartbeat, created_at, updated_at, completed_at
+            )
+            SELECT pg_notify('job_updates', row_to_json(updated)::text) FROM updated
             "#,
         )
         .bind(job_id)
@@ -367,17 +976,293 @@ impl Database {
     pub async fn fail_job(&self, job_id: i32) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            UPDATE user_jobs 
-            SET status = 'failed', completed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+            WITH updated AS (
+                UPDATE user_jobs
+                SET status = 'failed', completed_at = now(), updated_at = now()
+                WHERE id = $1
+                RETURNING id, user_id, status::text AS status, total_repos, processed_repos,
+                          failed_repos, worker_id, heartbeat, created_at, updated_at, completed_at
+            )
+            SELECT pg_notify('job_updates', row_to_json(updated)::text) FROM updated
+            "#,
+        )
+        .bind(job_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Subscribe to live job-progress updates emitted by [`Self::update_job_progress`],
+    /// [`Self::complete_job`], and [`Self::fail_job`].
+    ///
+    /// Runs `LISTEN job_updates` on a dedicated connection and yields each
+    /// deserialized [`UserJob`] as the notification arrives, so an SSE layer can
+    /// stream progress to the UI without polling. Payloads that fail to
+    /// deserialize are logged and skipped.
+    pub async fn watch_jobs(
+        &self,
+    ) -> Result<impl futures::Stream<Item = crate::types::UserJob>, sqlx::Error> {
+        use futures::StreamExt;
+
+        let mut listener = sqlx::postgres::PgListener::connect_with(&self.pool).await?;
+        listener.listen("job_updates").await?;
+
+        Ok(listener.into_stream().filter_map(|notification| async move {
+            match notification {
+                Ok(notification) => {
+                    match serde_json::from_str::<crate::types::UserJob>(notification.payload()) {
+                        Ok(job) => Some(job),
+                        Err(e) => {
+                            tracing::warn!("Failed to decode job_updates payload: {e}");
+                            None
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("job_updates listener error: {e}");
+                    None
+                }
+            }
+        }))
+    }
+
+    // ===== Work Queue Operations =====
+
+    /// Atomically claim the oldest pending job for `worker_id`.
+    ///
+    /// The claim is a single statement using `FOR UPDATE SKIP LOCKED`, so
+    /// concurrent workers never grab the same row and a contended pending queue
+    /// does not serialize workers against each other.
+    pub async fn claim_next_job(
+        &self,
+        worker_id: Uuid,
+    ) -> Result<Option<crate::types::UserJob>, sqlx::Error> {
+        let job = sqlx::query_as::<_, crate::types::UserJob>(
+            r#"
+            UPDATE user_jobs
+            SET status = 'running', worker_id = $1, heartbeat = now(), updated_at = now()
+            WHERE id = (
+                SELECT id FROM user_jobs
+                WHERE status = 'pending' AND not_before <= now()
+                ORDER BY created_at
+                FOR UPDATE SKIP LOCKED
+                LIMIT 1
+            )
+            RETURNING id, user_id, status::text AS status, total_repos, processed_repos,
+                      failed_repos, worker_id, heartbeat, created_at, updated_at, completed_at
+            "#,
+        )
+        .bind(worker_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(job)
+    }
+
+    /// Read a job's `(attempts, max_attempts)` so the caller can decide whether
+    /// another retry is allowed.
+    pub async fn job_retry_budget(
+        &self,
+        job_id: i32,
+    ) -> Result<Option<(i32, i32)>, sqlx::Error> {
+        let row = sqlx::query("SELECT attempts, max_attempts FROM user_jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| (r.get::<i32, _>("attempts"), r.get::<i32, _>("max_attempts"))))
+    }
+
+    /// Re-enqueue a failed job for a delayed retry: bump its attempt counter,
+    /// clear the owning worker, and hold it until `not_before` via the claim
+    /// query's scheduling guard.
+    pub async fn reschedule_job(
+        &self,
+        job_id: i32,
+        attempts: i32,
+        not_before: chrono::DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE user_jobs
+            SET status = 'pending', worker_id = NULL, heartbeat = NULL,
+                attempts = $2, not_before = $3, updated_at = now()
             WHERE id = $1
             "#,
         )
         .bind(job_id)
+        .bind(attempts)
+        .bind(not_before)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark a freshly-created job as running under `worker_id`, stamping an
+    /// initial heartbeat so crash recovery can track it.
+    pub async fn begin_job(&self, job_id: i32, worker_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE user_jobs
+            SET status = 'running', worker_id = $1, heartbeat = now(), updated_at = now()
+            WHERE id = $2
+            "#,
+        )
+        .bind(worker_id)
+        .bind(job_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Bump a running job's heartbeat, but only while `worker_id` still owns it.
+    ///
+    /// Returns `true` if the heartbeat was recorded; `false` means the job was
+    /// reclaimed or completed by someone else and the caller should stop.
+    pub async fn heartbeat_job(&self, job_id: i32, worker_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_jobs
+            SET heartbeat = now()
+            WHERE id = $1 AND worker_id = $2 AND status = 'running'
+            "#,
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Reset any running job whose heartbeat is older than `older_than` back to
+    /// pending, clearing its worker, so a crashed worker's jobs are recovered.
+    ///
+    /// Returns the number of jobs requeued.
+    pub async fn requeue_stale_jobs(&self, older_than: Duration) -> Result<u64, sqlx::Error> {
+        let cutoff = Utc::now()
+            - chrono::Duration::from_std(older_than).unwrap_or_else(|_| chrono::Duration::zero());
+        let result = sqlx::query(
+            r#"
+            UPDATE user_jobs
+            SET status = 'pending', worker_id = NULL, heartbeat = NULL, updated_at = now()
+            WHERE status = 'running' AND heartbeat < $1
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    // ===== Retry Queue Operations =====
+
+    /// Record a transient repository failure for later retry, tracking the attempt
+    /// count and the earliest timestamp at which it is eligible to run again.
+    pub async fn enqueue_repo_retry(
+        &self,
+        job_id: i32,
+        repo_id: Decimal,
+        attempts: i32,
+        next_attempt_at: chrono::DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO repo_retry_queue (job_id, repo_id, attempts, next_attempt_at, status)
+            VALUES ($1, $2, $3, $4, 'pending')
+            ON CONFLICT (job_id, repo_id)
+            DO UPDATE SET
+                attempts = EXCLUDED.attempts,
+                next_attempt_at = EXCLUDED.next_attempt_at,
+                status = 'pending'
+            "#,
+        )
+        .bind(job_id)
+        .bind(repo_id)
+        .bind(attempts)
+        .bind(next_attempt_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Fetch repositories for a job that are due for a retry, returning each
+    /// `(repo_id, attempts)` pair.
+    pub async fn due_repo_retries(
+        &self,
+        job_id: i32,
+        now: chrono::DateTime<Utc>,
+    ) -> Result<Vec<(Decimal, i32)>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT repo_id, attempts
+            FROM repo_retry_queue
+            WHERE job_id = $1 AND status = 'pending' AND next_attempt_at <= $2
+            ORDER BY next_attempt_at
+            "#,
+        )
+        .bind(job_id)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|r| (r.get::<Decimal, _>("repo_id"), r.get::<i32, _>("attempts")))
+            .collect())
+    }
+
+    /// Mark a repository retry as permanently failed (exhausted attempts or a
+    /// non-retryable error), so it is no longer re-picked.
+    pub async fn mark_repo_permanently_failed(
+        &self,
+        job_id: i32,
+        repo_id: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO repo_retry_queue (job_id, repo_id, attempts, next_attempt_at, status)
+            VALUES ($1, $2, 0, now(), 'permanently_failed')
+            ON CONFLICT (job_id, repo_id)
+            DO UPDATE SET status = 'permanently_failed'
+            "#,
+        )
+        .bind(job_id)
+        .bind(repo_id)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
+    /// Clear a repository from the retry queue once it has been processed.
+    pub async fn clear_repo_retry(&self, job_id: i32, repo_id: Decimal) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM repo_retry_queue WHERE job_id = $1 AND repo_id = $2")
+            .bind(job_id)
+            .bind(repo_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Aggregate retry health for a job: total retry attempts made, repositories
+    /// still pending a retry, and repositories permanently failed.
+    pub async fn retry_stats(&self, job_id: i32) -> Result<RetryStats, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COALESCE(SUM(attempts) FILTER (WHERE status = 'pending'), 0) AS retried,
+                COUNT(*) FILTER (WHERE status = 'pending') AS pending_retry,
+                COUNT(*) FILTER (WHERE status = 'permanently_failed') AS permanently_failed
+            FROM repo_retry_queue
+            WHERE job_id = $1
+            "#,
+        )
+        .bind(job_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(RetryStats {
+            retried: row.get::<i64, _>("retried"),
+            pending_retry: row.get::<i64, _>("pending_retry"),
+            permanently_failed: row.get::<i64, _>("permanently_failed"),
+        })
+    }
+
     // ===== Health Check Operations =====
 
     /// Test the database connection
@@ -391,17 +1276,68 @@ impl Database {
         }
     }
 
+    /// Read the declared dimension of the `repositories.embedding` vector
+    /// column, so the selected embedding backend can be validated against the
+    /// stored schema before any vectors are written.
+    pub async fn embedding_dimensions(&self) -> Result<Option<i32>, sqlx::Error> {
+        // pgvector records the typmod; `atttypmod` is the vector width directly.
+        sqlx::query_scalar::<_, Option<i32>>(
+            r#"
+            SELECT atttypmod
+            FROM pg_attribute
+            WHERE attrelid = 'repositories'::regclass
+              AND attname = 'embedding'
+              AND atttypmod > 0
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map(Option::flatten)
+    }
+
     // ===== Migration and Schema Operations =====
 
-    /// Run database migrations
+    /// Run any pending versioned migrations against the connected database.
+    ///
+    /// Fails fast with a clear error when the database is at an incompatible
+    /// version (a migration whose checksum no longer matches the embedded
+    /// source), rather than proceeding against an unexpected schema. The applied
+    /// migration versions are logged through `tracing`.
     pub async fn run_migrations(&self) -> Result<()> {
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to run migrations: {}", e))?;
+        let migrator = sqlx::migrate!("./migrations");
+        let applied_before = self.max_applied_migration().await;
+
+        migrator.run(&self.pool).await.map_err(|e| match e {
+            sqlx::migrate::MigrateError::VersionMismatch(version) => anyhow::anyhow!(
+                "Database is at an incompatible migration version: checksum mismatch at version {}. \
+                 Refusing to start against an unexpected schema.",
+                version
+            ),
+            other => anyhow::anyhow!("Failed to run migrations: {}", other),
+        })?;
+
+        let applied_after = self.max_applied_migration().await;
+        match (applied_before, applied_after) {
+            (before, Some(after)) if before != Some(after) => {
+                tracing::info!("Applied database migrations up to version {after}");
+            }
+            (_, Some(after)) => {
+                tracing::info!("Database schema already up to date at version {after}");
+            }
+            _ => tracing::info!("No database migrations to apply"),
+        }
         Ok(())
     }
 
+    /// Highest migration version recorded in `_sqlx_migrations`, if the table exists.
+    async fn max_applied_migration(&self) -> Option<i64> {
+        sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations")
+            .fetch_one(&self.pool)
+            .await
+            .ok()
+            .flatten()
+    }
+
     pub(crate) async fn existing_repos(
         &self,
         repo_ids: Vec<Decimal>,
@@ -0,0 +1,80 @@
+// Account-eligibility rules.
+//
+// Access to a star scan is gated on the caller's GitHub account being
+// "established" enough: following a minimum number of accounts, old enough, and
+// having starred enough repositories. The evaluation returns a structured
+// decision carrying every failed threshold so the frontend can tell the user
+// exactly what they're missing rather than a flat 403.
+
+use chrono::{Duration, Utc};
+use serde::Serialize;
+
+use crate::types::OAuthCacheObject;
+
+/// The caller's starred-repository count, injected into request extensions by
+/// the auth middleware alongside the [`OAuthCacheObject`].
+#[derive(Debug, Clone, Copy)]
+pub struct StarCount(pub u32);
+
+/// Thresholds a caller must clear to be eligible, sourced from configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct EligibilityRules {
+    /// Minimum number of accounts the user must follow.
+    pub min_following: i32,
+    /// Minimum account age.
+    pub min_account_age: Duration,
+    /// Minimum number of repositories the user must have starred.
+    pub min_stars: u32,
+}
+
+/// A single failed threshold, paired with the observed value, so the caller can
+/// render "needs N, has M" feedback.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum RejectionReason {
+    Following { required: i32, actual: i32 },
+    AccountAge { required_days: i64, actual_days: i64 },
+    Stars { required: u32, actual: u32 },
+}
+
+/// The outcome of evaluating [`EligibilityRules`] against a user.
+#[derive(Debug, Clone, Serialize)]
+pub struct EligibilityDecision {
+    pub allowed: bool,
+    pub reasons: Vec<RejectionReason>,
+}
+
+impl EligibilityDecision {
+    /// Evaluate every rule, collecting each failed threshold. A user is allowed
+    /// only when no rule is violated.
+    pub fn evaluate(rules: &EligibilityRules, oauth: &OAuthCacheObject, star_count: u32) -> Self {
+        let mut reasons = Vec::new();
+
+        if oauth.following_count < rules.min_following {
+            reasons.push(RejectionReason::Following {
+                required: rules.min_following,
+                actual: oauth.following_count,
+            });
+        }
+
+        let age = Utc::now() - oauth.created_at;
+        if age < rules.min_account_age {
+            reasons.push(RejectionReason::AccountAge {
+                required_days: rules.min_account_age.num_days(),
+                actual_days: age.num_days(),
+            });
+        }
+
+        if star_count < rules.min_stars {
+            reasons.push(RejectionReason::Stars {
+                required: rules.min_stars,
+                actual: star_count,
+            });
+        }
+
+        EligibilityDecision {
+            allowed: reasons.is_empty(),
+            reasons,
+        }
+    }
+}
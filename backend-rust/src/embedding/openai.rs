@@ -3,8 +3,49 @@ use async_openai::{
     config::OpenAIConfig,
     types::{CreateEmbeddingRequest, EmbeddingInput},
 };
+use async_trait::async_trait;
 use thiserror::Error;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// Whether an OpenAI error is worth retrying: a rate-limit (429) response or a
+/// transient transport failure.
+fn is_rate_limited(err: &async_openai::error::OpenAIError) -> bool {
+    use async_openai::error::OpenAIError;
+    match err {
+        OpenAIError::ApiError(e) => e
+            .code
+            .as_deref()
+            .map(|c| c.contains("429") || c.eq_ignore_ascii_case("rate_limit_exceeded"))
+            .unwrap_or(false)
+            || e.r#type.as_deref() == Some("rate_limit_exceeded"),
+        // Transient transport failures are also safe to retry.
+        OpenAIError::Reqwest(_) => true,
+        _ => false,
+    }
+}
+
+/// Best-effort extraction of a `Retry-After` delay from an OpenAI error.
+///
+/// The client surfaces the server message but not response headers, so we
+/// parse an explicit "try again in Ns" hint when present and otherwise let the
+/// caller fall back to exponential backoff.
+fn retry_after(err: &async_openai::error::OpenAIError) -> Option<std::time::Duration> {
+    use async_openai::error::OpenAIError;
+    let OpenAIError::ApiError(e) = err else {
+        return None;
+    };
+    let msg = &e.message;
+    let idx = msg.find("try again in")?;
+    let rest = &msg[idx + "try again in".len()..];
+    let secs: String = rest
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    secs.parse::<f64>()
+        .ok()
+        .map(std::time::Duration::from_secs_f64)
+}
 
 #[derive(Error, Debug)]
 pub enum EmbeddingError {
@@ -16,9 +57,53 @@ pub enum EmbeddingError {
     ValidationError(String),
 }
 
+/// Backend-agnostic interface for generating text embeddings.
+///
+/// Implementing this trait lets Starscout point at local/self-hosted embedding
+/// servers or other vendors without touching the search logic.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync + std::fmt::Debug {
+    /// Generate an embedding for a single text.
+    async fn get_embedding(&self, text: &str, api_key: &str) -> Result<Vec<f32>, EmbeddingError>;
+
+    /// Generate embeddings for multiple texts.
+    async fn get_embeddings(
+        &self,
+        texts: Vec<String>,
+        api_key: &str,
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError>;
+
+    /// Dimensionality of the vectors this backend produces. Validated against
+    /// the stored schema at startup so a mismatched backend fails fast rather
+    /// than corrupting the index.
+    fn dimensions(&self) -> usize;
+
+    /// Identifier of the underlying model, for logging and diagnostics.
+    fn model_id(&self) -> &str;
+
+    /// Maximum number of (estimated) tokens that may be packed into a single
+    /// embedding request.
+    fn max_tokens_per_batch(&self) -> usize;
+
+    /// Truncate `span` so its estimated token count fits within
+    /// [`Self::max_tokens_per_batch`], returning the (possibly truncated) text and
+    /// its estimated token count.
+    fn truncate(&self, span: &str) -> (String, usize);
+}
+
 #[derive(Debug, Clone)]
 pub struct OpenAIEmbeddingService {
     model: String,
+    dimensions: usize,
+    /// Optional base URL override so OpenAI-compatible servers (local inference,
+    /// alternative vendors) speaking the same `/v1/embeddings` API can be used.
+    base_url: Option<String>,
+    max_tokens_per_batch: usize,
+    /// Hard cap on the number of inputs packed into one `/v1/embeddings` call,
+    /// independent of the token budget.
+    max_items_per_request: usize,
+    /// Maximum number of sub-batch requests in flight at once.
+    max_concurrent_requests: usize,
 }
 
 impl Default for OpenAIEmbeddingService {
@@ -28,12 +113,26 @@ impl Default for OpenAIEmbeddingService {
 }
 
 impl OpenAIEmbeddingService {
+    /// Rough token estimate: OpenAI's tokenizer averages ~4 characters per token.
+    const CHARS_PER_TOKEN: usize = 4;
+
+    /// Maximum number of retries for a rate-limited sub-batch.
+    const MAX_RETRIES: u32 = 5;
+
+    /// Base delay for exponential backoff when no `Retry-After` is provided.
+    const BASE_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+
     /// Create a new OpenAI embedding service.
     ///
     /// It will use the `OPENAI_API_KEY` environment variable if present.
     pub fn new() -> Self {
         Self {
             model: "text-embedding-3-small".to_string(),
+            dimensions: 1536,
+            base_url: None,
+            max_tokens_per_batch: 8000,
+            max_items_per_request: 2048,
+            max_concurrent_requests: 4,
         }
     }
 
@@ -43,6 +142,39 @@ impl OpenAIEmbeddingService {
         self
     }
 
+    /// Create service with a custom embedding dimension
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = dimensions;
+        self
+    }
+
+    /// Whether the configured model accepts the `dimensions` request
+    /// parameter. Only the `text-embedding-3-*` family supports it; older
+    /// models such as `text-embedding-ada-002` reject it with HTTP 400.
+    fn model_supports_dimensions(&self) -> bool {
+        self.model.starts_with("text-embedding-3")
+    }
+
+    /// Point the service at an OpenAI-compatible server other than api.openai.com.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        self.base_url = if base_url.is_empty() {
+            None
+        } else {
+            Some(base_url)
+        };
+        self
+    }
+
+    /// Build an OpenAI client config, applying the base-URL override if set.
+    fn client_config(&self, api_key: &str) -> OpenAIConfig {
+        let mut config = OpenAIConfig::new().with_api_key(api_key.to_string());
+        if let Some(base_url) = &self.base_url {
+            config = config.with_api_base(base_url.clone());
+        }
+        config
+    }
+
     /// Generate embedding for a single text
     pub async fn get_embedding(
         &self,
@@ -83,55 +215,171 @@ impl OpenAIEmbeddingService {
 
         debug!("Getting embeddings for {} texts", texts.len());
 
-        let all_embeddings = self.get_embeddings_batch(texts, api_key).await?;
+        // Split into sub-batches bounded by both the estimated token budget and
+        // the per-request item cap, so no single call can exceed the model's
+        // limits and get truncated or rejected.
+        let batches = self.plan_batches(&texts);
+        debug!(
+            "Planned {} embedding sub-batch(es) for {} texts",
+            batches.len(),
+            texts.len()
+        );
+
+        // Issue the sub-batches concurrently with a bounded in-flight limit,
+        // keeping each batch tagged with its starting offset for reassembly.
+        let results: Vec<Result<(usize, Vec<Vec<f32>>), EmbeddingError>> =
+            futures::stream::iter(batches.into_iter().map(|(offset, batch)| async move {
+                let embeddings = self.get_embeddings_batch(batch, api_key).await?;
+                Ok((offset, embeddings))
+            }))
+            .buffer_unordered(self.max_concurrent_requests)
+            .collect()
+            .await;
+
+        // Reassemble into the original input order.
+        let mut all_embeddings: Vec<Vec<f32>> = vec![Vec::new(); texts.len()];
+        for result in results {
+            let (offset, embeddings) = result?;
+            for (i, embedding) in embeddings.into_iter().enumerate() {
+                all_embeddings[offset + i] = embedding;
+            }
+        }
 
         info!("Successfully generated {} embeddings", all_embeddings.len());
         Ok(all_embeddings)
     }
 
-    /// Get embeddings for a single batch
+    /// Partition `texts` into `(start_offset, sub_batch)` groups that each fit
+    /// within [`Self::max_tokens_per_batch`] (estimated via the chars/4
+    /// heuristic) and [`Self::max_items_per_request`]. A single text estimated
+    /// above the token budget is not split; it forms a batch of its own.
+    fn plan_batches(&self, texts: &[String]) -> Vec<(usize, Vec<String>)> {
+        let mut batches = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut current_start = 0;
+        let mut current_tokens = 0;
+
+        for (i, text) in texts.iter().enumerate() {
+            let tokens = text.chars().count().div_ceil(Self::CHARS_PER_TOKEN);
+            let would_overflow = current_tokens + tokens > self.max_tokens_per_batch
+                || current.len() >= self.max_items_per_request;
+            if would_overflow && !current.is_empty() {
+                batches.push((current_start, std::mem::take(&mut current)));
+                current_start = i;
+                current_tokens = 0;
+            }
+            current.push(text.clone());
+            current_tokens += tokens;
+        }
+        if !current.is_empty() {
+            batches.push((current_start, current));
+        }
+        batches
+    }
+
+    /// Get embeddings for a single sub-batch, retrying rate-limited responses
+    /// and transient transport failures with exponential backoff that honors any `Retry-After` hint.
     pub async fn get_embeddings_batch(
         &self,
         texts: Vec<String>,
         api_key: &str,
     ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
-        let config = OpenAIConfig::new().with_api_key(api_key.to_string());
-        let client = OpenAIClient::with_config(config);
-
-        let request = CreateEmbeddingRequest {
-            model: self.model.clone(),
-            input: EmbeddingInput::StringArray(texts.clone()),
-            encoding_format: None,
-            dimensions: None,
-            user: None,
-        };
+        let client = OpenAIClient::with_config(self.client_config(api_key));
 
-        debug!("Making OpenAI embedding request for {} texts", texts.len());
+        let mut attempt = 0;
+        loop {
+            let request = CreateEmbeddingRequest {
+                model: self.model.clone(),
+                input: EmbeddingInput::StringArray(texts.clone()),
+                encoding_format: None,
+                dimensions: if self.model_supports_dimensions() {
+                    Some(self.dimensions as u32)
+                } else {
+                    None
+                },
+                user: None,
+            };
 
-        let response = client.embeddings().create(request).await?;
+            debug!("Making OpenAI embedding request for {} texts", texts.len());
 
-        debug!("Received {} embeddings from OpenAI", response.data.len());
+            match client.embeddings().create(request).await {
+                Ok(response) => {
+                    debug!("Received {} embeddings from OpenAI", response.data.len());
+                    let embeddings: Vec<Vec<f32>> = response
+                        .data
+                        .into_iter()
+                        .map(|embedding| embedding.embedding)
+                        .collect();
 
-        // Extract embeddings from response
-        let embeddings: Vec<Vec<f32>> = response
-            .data
-            .into_iter()
-            .map(|embedding| embedding.embedding)
-            .collect();
-
-        if embeddings.len() != texts.len() {
-            return Err(EmbeddingError::ValidationError(format!(
-                "Expected {} embeddings, got {}",
-                texts.len(),
-                embeddings.len()
-            )));
+                    if embeddings.len() != texts.len() {
+                        return Err(EmbeddingError::ValidationError(format!(
+                            "Expected {} embeddings, got {}",
+                            texts.len(),
+                            embeddings.len()
+                        )));
+                    }
+                    return Ok(embeddings);
+                }
+                Err(e) if is_rate_limited(&e) && attempt < Self::MAX_RETRIES => {
+                    let backoff = retry_after(&e)
+                        .unwrap_or_else(|| Self::BASE_RETRY_BACKOFF * (1 << attempt));
+                    warn!(
+                        "OpenAI rate-limited embedding sub-batch; retrying in {:?} (attempt {})",
+                        backoff,
+                        attempt + 1
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
         }
-
-        Ok(embeddings)
     }
 
     /// Get the model being used
     pub fn model(&self) -> &str {
         &self.model
     }
+
+    /// Get the embedding dimension produced by this service
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddingService {
+    async fn get_embedding(&self, text: &str, api_key: &str) -> Result<Vec<f32>, EmbeddingError> {
+        OpenAIEmbeddingService::get_embedding(self, text, api_key).await
+    }
+
+    async fn get_embeddings(
+        &self,
+        texts: Vec<String>,
+        api_key: &str,
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        OpenAIEmbeddingService::get_embeddings(self, texts, api_key).await
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+
+    fn max_tokens_per_batch(&self) -> usize {
+        self.max_tokens_per_batch
+    }
+
+    fn truncate(&self, span: &str) -> (String, usize) {
+        let max_chars = self.max_tokens_per_batch * Self::CHARS_PER_TOKEN;
+        if span.chars().count() <= max_chars {
+            let tokens = span.chars().count().div_ceil(Self::CHARS_PER_TOKEN);
+            return (span.to_string(), tokens);
+        }
+        let truncated: String = span.chars().take(max_chars).collect();
+        (truncated, self.max_tokens_per_batch)
+    }
 }
@@ -1,9 +1,16 @@
-use axum::{extract::FromRequestParts, response::Response};
-use http::request::Parts;
+use axum::{
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+    Json,
+};
+use http::{request::Parts, StatusCode};
+use serde_json::json;
 
 use crate::{
+    eligibility::{EligibilityDecision, EligibilityRules, StarCount},
     github::{Author, GitHubClient},
     http::unauthorized,
+    types::OAuthCacheObject,
 };
 // use crate::http::unauthorized;
 // use axum::{extract::FromRequestParts, http::request::Parts, response::Response};
@@ -78,3 +85,58 @@ where
         })
     }
 }
+
+/// Extractor that admits only callers who clear the configured eligibility
+/// thresholds.
+///
+/// Builds on [`AuthenticatedContext`], then evaluates [`EligibilityRules`]
+/// against the cached OAuth record and star count injected by the auth
+/// middleware. Ineligible callers are rejected with a `403` whose JSON body
+/// enumerates each failed threshold and by how much, so the frontend can show
+/// actionable guidance.
+#[derive(Debug, Clone)]
+pub struct EligibleUser {
+    pub context: AuthenticatedContext,
+    pub oauth: OAuthCacheObject,
+}
+
+impl<S> FromRequestParts<S> for EligibleUser
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let context = AuthenticatedContext::from_request_parts(parts, state).await?;
+
+        let rules = parts
+            .extensions
+            .get::<EligibilityRules>()
+            .copied()
+            .ok_or_else(|| unauthorized("Eligibility rules not found in request extensions"))?;
+
+        let oauth = parts
+            .extensions
+            .get::<OAuthCacheObject>()
+            .cloned()
+            .ok_or_else(|| unauthorized("OAuth record not found in request extensions"))?;
+
+        let star_count = parts
+            .extensions
+            .get::<StarCount>()
+            .copied()
+            .map(|s| s.0)
+            .unwrap_or_default();
+
+        let decision = EligibilityDecision::evaluate(&rules, &oauth, star_count);
+        if !decision.allowed {
+            let body = Json(json!({
+                "error": "account_not_eligible",
+                "reasons": decision.reasons,
+            }));
+            return Err((StatusCode::FORBIDDEN, body).into_response());
+        }
+
+        Ok(EligibleUser { context, oauth })
+    }
+}
@@ -0,0 +1,258 @@
+// Per-resource response cache for the GitHub client.
+//
+// GitHub's conditional-request support means a revalidation that returns `304
+// Not Modified` does not count against the primary rate limit, so caching the
+// ETag of every resource we fetch and replaying it via `If-None-Match` lets
+// repeated star-scans reuse prior responses almost for free.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Logical namespaces kept in separate keyspaces so unrelated resources with
+/// colliding identifiers (a user and a repo sharing a numeric id) never clash,
+/// and so each can carry its own TTL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    Users,
+    Repos,
+    Contributors,
+    Commits,
+    Releases,
+}
+
+impl Namespace {
+    /// Stable string used as the on-disk subdirectory / Redis key prefix.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Namespace::Users => "users",
+            Namespace::Repos => "repos",
+            Namespace::Contributors => "contributors",
+            Namespace::Commits => "commits",
+            Namespace::Releases => "releases",
+        }
+    }
+}
+
+/// A cached GitHub response: the validator plus the value and when it was last
+/// fetched or revalidated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedEntry {
+    pub etag: String,
+    pub value: serde_json::Value,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CachedEntry {
+    /// Whether the entry is older than `ttl` and should be revalidated.
+    pub fn is_stale(&self, ttl: Duration) -> bool {
+        Utc::now() - self.fetched_at > ttl
+    }
+}
+
+/// Pluggable storage backend for [`CachedEntry`] values.
+#[async_trait]
+pub trait Cache: Send + Sync + std::fmt::Debug {
+    /// Load the entry for `(namespace, key)`, if present.
+    async fn get(&self, namespace: Namespace, key: &str) -> Option<CachedEntry>;
+
+    /// Store (or replace) the entry for `(namespace, key)`.
+    async fn put(&self, namespace: Namespace, key: &str, entry: CachedEntry);
+
+    /// Refresh an entry's `fetched_at` after a `304 Not Modified`, without
+    /// rewriting the value.
+    async fn touch(&self, namespace: Namespace, key: &str) {
+        if let Some(mut entry) = self.get(namespace, key).await {
+            entry.fetched_at = Utc::now();
+            self.put(namespace, key, entry).await;
+        }
+    }
+}
+
+/// Filesystem cache storing one JSON file per entry under `<dir>/<namespace>/`.
+#[derive(Debug, Clone)]
+pub struct FilesystemCache {
+    dir: PathBuf,
+}
+
+impl FilesystemCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Path of the JSON file backing `(namespace, key)`, with the key sanitized
+    /// so slashes in `owner/name` don't escape the namespace directory.
+    fn path(&self, namespace: Namespace, key: &str) -> PathBuf {
+        let safe_key = key.replace(['/', '\\'], "_");
+        self.dir.join(namespace.as_str()).join(format!("{safe_key}.json"))
+    }
+}
+
+#[async_trait]
+impl Cache for FilesystemCache {
+    async fn get(&self, namespace: Namespace, key: &str) -> Option<CachedEntry> {
+        let path = self.path(namespace, key);
+        let bytes = tokio::fs::read(&path).await.ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    async fn put(&self, namespace: Namespace, key: &str, entry: CachedEntry) {
+        let path = self.path(namespace, key);
+        if let Some(parent) = path.parent() {
+            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                tracing::warn!("Failed to create cache dir {}: {}", parent.display(), e);
+                return;
+            }
+        }
+        match serde_json::to_vec(&entry) {
+            Ok(bytes) => {
+                if let Err(e) = tokio::fs::write(&path, bytes).await {
+                    tracing::warn!("Failed to write cache entry {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize cache entry: {e}"),
+        }
+    }
+}
+
+/// Redis cache reusing the connection manager already used for the OAuth cache.
+#[derive(Clone)]
+pub struct RedisCache {
+    connection: redis::aio::ConnectionManager,
+}
+
+impl std::fmt::Debug for RedisCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisCache").finish_non_exhaustive()
+    }
+}
+
+impl RedisCache {
+    pub fn new(connection: redis::aio::ConnectionManager) -> Self {
+        Self { connection }
+    }
+
+    fn redis_key(namespace: Namespace, key: &str) -> String {
+        format!("ghcache:{}:{}", namespace.as_str(), key)
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, namespace: Namespace, key: &str) -> Option<CachedEntry> {
+        let mut conn = self.connection.clone();
+        let raw: Option<String> = redis::cmd("GET")
+            .arg(Self::redis_key(namespace, key))
+            .query_async(&mut conn)
+            .await
+            .ok()?;
+        raw.and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    async fn put(&self, namespace: Namespace, key: &str, entry: CachedEntry) {
+        let mut conn = self.connection.clone();
+        let Ok(raw) = serde_json::to_string(&entry) else {
+            return;
+        };
+        let _: Result<(), _> = redis::cmd("SET")
+            .arg(Self::redis_key(namespace, key))
+            .arg(raw)
+            .query_async::<()>(&mut conn)
+            .await;
+    }
+}
+
+/// Per-namespace TTLs controlling when a cached entry is revalidated.
+#[derive(Debug, Clone)]
+pub struct CacheTtls {
+    ttls: HashMap<Namespace, Duration>,
+    default: Duration,
+}
+
+impl CacheTtls {
+    pub fn new(default: Duration) -> Self {
+        Self {
+            ttls: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Override the TTL for a single namespace.
+    pub fn with(mut self, namespace: Namespace, ttl: Duration) -> Self {
+        self.ttls.insert(namespace, ttl);
+        self
+    }
+
+    /// Resolve the TTL for `namespace`, falling back to the default.
+    pub fn get(&self, namespace: Namespace) -> Duration {
+        self.ttls.get(&namespace).copied().unwrap_or(self.default)
+    }
+}
+
+/// A cache handle paired with its TTL policy, as held by the GitHub client.
+#[derive(Debug, Clone)]
+pub struct ResourceCache {
+    backend: Arc<dyn Cache>,
+    ttls: CacheTtls,
+    /// Guards `put` races when two requests revalidate the same key at once.
+    locks: Arc<Mutex<()>>,
+}
+
+impl ResourceCache {
+    pub fn new(backend: Arc<dyn Cache>, ttls: CacheTtls) -> Self {
+        Self {
+            backend,
+            ttls,
+            locks: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// The ETag to send as `If-None-Match`, if a non-stale entry exists.
+    pub async fn validator(&self, namespace: Namespace, key: &str) -> Option<String> {
+        let entry = self.backend.get(namespace, key).await?;
+        if entry.is_stale(self.ttls.get(namespace)) {
+            None
+        } else {
+            Some(entry.etag)
+        }
+    }
+
+    /// Serve the cached value after a `304`, bumping its freshness.
+    pub async fn serve_not_modified(
+        &self,
+        namespace: Namespace,
+        key: &str,
+    ) -> Option<serde_json::Value> {
+        let entry = self.backend.get(namespace, key).await?;
+        self.backend.touch(namespace, key).await;
+        Some(entry.value)
+    }
+
+    /// Replace the cached entry after a `200` with a fresh ETag and value.
+    pub async fn store(
+        &self,
+        namespace: Namespace,
+        key: &str,
+        etag: String,
+        value: serde_json::Value,
+    ) {
+        let _guard = self.locks.lock().await;
+        self.backend
+            .put(
+                namespace,
+                key,
+                CachedEntry {
+                    etag,
+                    value,
+                    fetched_at: Utc::now(),
+                },
+            )
+            .await;
+    }
+}
@@ -1,16 +1,55 @@
-// GitHub REST API client using reqwest will go here
+// GitHub client backed by the GraphQL v4 API
 
-use base64::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::TryStreamExt;
 use octocrab::models::{Author, Repository};
 use octocrab::{Error as OctocrabError, Octocrab};
-use url::Url;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::cache::{Namespace, ResourceCache};
+use super::ratelimit::{retry_after, RateLimitInfo, RateLimitPolicy};
 
-/// A thin wrapper around the octocrab crate to expose only the project-specific
-/// GitHub operations we need (fetching the authenticated user, paginated starred
-/// repositories, and repository README content).
+/// GraphQL query fetching a page of the viewer's starred repositories together
+/// with each repository's `README.md` contents in a single round-trip.
+const STARRED_REPOS_QUERY: &str = r#"
+query($cursor: String) {
+  viewer {
+    starredRepositories(first: 100, after: $cursor, orderBy: {field: STARRED_AT, direction: DESC}) {
+      pageInfo { endCursor hasNextPage }
+      nodes {
+        databaseId
+        nameWithOwner
+        description
+        url
+        stargazerCount
+        createdAt
+        updatedAt
+        primaryLanguage { name }
+        repositoryTopics(first: 20) { nodes { topic { name } } }
+        object(expression: "HEAD:README.md") { ... on Blob { text } }
+      }
+    }
+  }
+}
+"#;
+
+/// A thin wrapper around the octocrab crate that ingests the authenticated
+/// user's stars via a single cursor-driven GraphQL loop. README contents fetched
+/// alongside the star list are cached so [`GitHubClient::get_readme`] serves them
+/// without an extra REST round-trip.
 #[derive(Debug, Clone)]
 pub struct GitHubClient {
     inner: Octocrab,
+    /// README text keyed by `owner/name`, populated while listing stars.
+    readme_cache: Arc<Mutex<HashMap<String, Option<String>>>>,
+    /// Conditional-request cache shared across REST resource fetches. Absent
+    /// when caching is disabled in configuration.
+    cache: Option<ResourceCache>,
+    /// How the client waits out primary and secondary rate limits.
+    policy: RateLimitPolicy,
 }
 
 impl GitHubClient {
@@ -18,7 +57,189 @@ impl GitHubClient {
     pub fn new(token: impl Into<String>) -> Result<Self, OctocrabError> {
         let inner = Octocrab::builder().personal_token(token.into()).build()?;
 
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            readme_cache: Arc::new(Mutex::new(HashMap::new())),
+            cache: None,
+            policy: RateLimitPolicy::default(),
+        })
+    }
+
+    /// Attach a [`ResourceCache`] so subsequent REST resource fetches revalidate
+    /// with `If-None-Match` and reuse unchanged responses.
+    pub fn with_cache(mut self, cache: ResourceCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Override the rate-limit policy governing automatic backoff.
+    pub fn with_rate_limit_policy(mut self, policy: RateLimitPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Execute a REST request, transparently waiting out rate limits.
+    ///
+    /// When the primary window is exhausted the call sleeps until it resets
+    /// (capped by [`RateLimitPolicy::max_sleep`]); secondary limits (`403`/`429`
+    /// with `Retry-After`) are retried with exponential backoff and jitter up to
+    /// [`RateLimitPolicy::max_retries`] times.
+    async fn execute_rest(
+        &self,
+        request: http::Request<String>,
+    ) -> Result<http::Response<Vec<u8>>, OctocrabError> {
+        let mut attempt = 0u32;
+        loop {
+            // `http::Request` isn't `Clone`; rebuild it for each retry.
+            let mut builder = http::Request::builder()
+                .method(request.method())
+                .uri(request.uri());
+            for (name, value) in request.headers() {
+                builder = builder.header(name, value);
+            }
+            let attempt_request = builder
+                .body(request.body().clone())
+                .map_err(|e| graphql_error(e.to_string()))?;
+
+            let response = self.inner.execute(attempt_request).await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+
+            let secondary = status == http::StatusCode::TOO_MANY_REQUESTS
+                || (status == http::StatusCode::FORBIDDEN && retry_after(&headers).is_some());
+
+            if secondary && attempt < self.policy.max_retries {
+                let seed = jitter_seed(request.uri().path(), attempt);
+                let delay = self.policy.backoff(attempt, retry_after(&headers), seed);
+                tracing::warn!(
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "GitHub secondary rate limit hit; backing off"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+
+            // Primary limit: when the window is drained, pause until it resets so
+            // the next call succeeds instead of failing the whole scan.
+            if let Some(info) = RateLimitInfo::from_headers(&headers) {
+                if info.remaining == 0 {
+                    if let Some(sleep) = info.sleep_until_reset() {
+                        if sleep <= self.policy.max_sleep {
+                            tracing::warn!(
+                                sleep_ms = sleep.as_millis() as u64,
+                                "GitHub primary rate limit exhausted; sleeping until reset"
+                            );
+                            tokio::time::sleep(sleep).await;
+                        }
+                    }
+                }
+            }
+
+            return Ok(response);
+        }
+    }
+
+    /// Fetch a REST resource under `namespace`, revalidating against the cache
+    /// with a conditional request when a prior, non-stale ETag is known.
+    ///
+    /// On `304 Not Modified` the cached value is deserialized and returned
+    /// without re-downloading the body; on `200` the fresh ETag and JSON are
+    /// stored before deserializing. With no cache attached this degrades to a
+    /// plain GET.
+    pub async fn fetch_with_cache<T>(
+        &self,
+        namespace: Namespace,
+        key: &str,
+        route: &str,
+    ) -> Result<T, OctocrabError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let Some(cache) = &self.cache else {
+            return self.inner.get(route, None::<&()>).await;
+        };
+
+        let mut builder = http::Request::builder().method("GET").uri(route);
+        if let Some(etag) = cache.validator(namespace, key).await {
+            builder = builder.header(http::header::IF_NONE_MATCH, etag);
+        }
+        let request = builder
+            .body(String::new())
+            .map_err(|e| graphql_error(e.to_string()))?;
+
+        let response = self.execute_rest(request).await?;
+        let status = response.status();
+        let etag = response
+            .headers()
+            .get(http::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        if status == http::StatusCode::NOT_MODIFIED {
+            if let Some(value) = cache.serve_not_modified(namespace, key).await {
+                return serde_json::from_value(value).map_err(|e| OctocrabError::Serde {
+                    source: e,
+                    backtrace: std::backtrace::Backtrace::capture(),
+                });
+            }
+        }
+
+        let body = response.into_body();
+        let value: serde_json::Value = serde_json::from_slice(&body).map_err(|e| {
+            OctocrabError::Serde {
+                source: e,
+                backtrace: std::backtrace::Backtrace::capture(),
+            }
+        })?;
+        if let Some(etag) = etag {
+            cache.store(namespace, key, etag, value.clone()).await;
+        }
+        serde_json::from_value(value).map_err(|e| OctocrabError::Serde {
+            source: e,
+            backtrace: std::backtrace::Backtrace::capture(),
+        })
+    }
+
+    /// Stream every item of a paginated REST collection, following
+    /// `Link: rel="next"` headers.
+    ///
+    /// Each yielded page is deserialized as `Vec<T>` and flattened, so callers
+    /// fetching a full starred-repo list or follower set can consume a
+    /// `Stream<Item = Result<T>>` without managing cursors themselves. Rate
+    /// limits between pages are handled by [`GitHubClient::execute_rest`].
+    pub fn paginate<T>(
+        &self,
+        first_route: impl Into<String>,
+    ) -> impl futures::Stream<Item = Result<T, OctocrabError>> + '_
+    where
+        T: serde::de::DeserializeOwned + 'static,
+    {
+        let state = Some(first_route.into());
+        futures::stream::try_unfold(state, move |next| async move {
+            let Some(route) = next else {
+                return Ok(None);
+            };
+
+            let request = http::Request::builder()
+                .method("GET")
+                .uri(&route)
+                .body(String::new())
+                .map_err(|e| graphql_error(e.to_string()))?;
+
+            let response = self.execute_rest(request).await?;
+            let next_route = next_link(response.headers());
+            let body = response.into_body();
+            let items: Vec<T> =
+                serde_json::from_slice(&body).map_err(|e| OctocrabError::Serde {
+                    source: e,
+                    backtrace: std::backtrace::Backtrace::capture(),
+                })?;
+
+            Ok(Some((futures::stream::iter(items.into_iter().map(Ok)), next_route)))
+        })
+        .try_flatten()
     }
 
     /// Get the authenticated user information
@@ -27,110 +248,266 @@ impl GitHubClient {
         Ok(response)
     }
 
-    pub async fn get_starred_repos_count(&self) -> Result<usize, OctocrabError> {
-        let response = self
-            .inner
-            .current()
-            .list_repos_starred_by_authenticated_user()
-            .per_page(1)
-            .send()
-            .await?;
-
-        let last_page = response.last.unwrap();
-        let star_count = get_page_from_url(&last_page.to_string()).unwrap();
-        Ok(star_count.try_into().unwrap())
-    }
+    /// Get all starred repositories for the authenticated user.
+    ///
+    /// Walks `viewer.starredRepositories` page by page via `pageInfo.endCursor`,
+    /// mapping each node into the `octocrab::models::Repository` shape the rest of
+    /// the pipeline already consumes and caching the README fetched in the same
+    /// request.
+    pub async fn get_starred_repos(&self) -> Result<Vec<Repository>, OctocrabError> {
+        let mut cursor: Option<String> = None;
+        let mut all_repos = Vec::new();
 
-    /// Get all starred repositories for the authenticated user
-    /// This method fetches the first page to get total count, then fetches all remaining pages in parallel
-    pub async fn get_starred_repos(
-        &self,
-        star_count: usize,
-    ) -> Result<Vec<Repository>, OctocrabError> {
-        let per_page = 100u8;
-
-        let pages = (star_count as f64 / per_page as f64).ceil() as u8;
-        let mut handles = Vec::new();
-
-        // Spawn a task for each page
-        for page in 1..=pages {
-            let client = self.inner.clone();
-            let handle = tokio::spawn(async move {
-                client
-                    .current()
-                    .list_repos_starred_by_authenticated_user()
-                    .per_page(per_page)
-                    .page(page)
-                    .send()
-                    .await
+        loop {
+            let body = json!({
+                "query": STARRED_REPOS_QUERY,
+                "variables": { "cursor": cursor },
             });
-            handles.push(handle);
-        }
 
-        // Collect results from all tasks
-        let mut all_repos = Vec::new();
-        for handle in handles {
-            let page_result = handle.await.map_err(|e| OctocrabError::Serde {
-                source: serde_json::Error::io(std::io::Error::other(format!(
-                    "Task join error: {e}"
-                ))),
-                backtrace: std::backtrace::Backtrace::capture(),
-            })??;
+            let response: GraphQLResponse = self.inner.graphql(&body).await?;
+            if let Some(errors) = response.errors {
+                if let Some(first) = errors.into_iter().next() {
+                    return Err(graphql_error(first.message));
+                }
+            }
 
-            all_repos.extend(page_result.items);
+            let Some(page) = response.data.map(|d| d.viewer.starred_repositories) else {
+                break;
+            };
+
+            for node in page.nodes {
+                let key = node.name_with_owner.clone();
+                let readme = node.object.as_ref().and_then(|o| o.text.clone());
+                self.readme_cache
+                    .lock()
+                    .expect("readme cache poisoned")
+                    .insert(key, readme);
+                all_repos.push(node.into_repository()?);
+            }
+
+            if page.page_info.has_next_page {
+                cursor = page.page_info.end_cursor;
+            } else {
+                break;
+            }
         }
 
         Ok(all_repos)
     }
 
     /// Get the README content for a specific repository.
-    /// Returns the raw markdown content as a Some(string) if found, None otherwise.
+    ///
+    /// Returns the README captured during [`GitHubClient::get_starred_repos`] when
+    /// available, falling back to a GraphQL lookup for repositories that were not
+    /// part of a star-listing pass.
     pub async fn get_readme(
         &self,
         owner: &str,
         repo: &str,
     ) -> Result<Option<String>, OctocrabError> {
-        let res = self.inner.repos(owner, repo).get_readme().send().await?;
+        let key = format!("{owner}/{repo}");
+        if let Some(cached) = self
+            .readme_cache
+            .lock()
+            .expect("readme cache poisoned")
+            .get(&key)
+            .cloned()
+        {
+            return Ok(cached);
+        }
+
+        let body = json!({
+            "query": r#"
+                query($owner: String!, $name: String!) {
+                  repository(owner: $owner, name: $name) {
+                    object(expression: "HEAD:README.md") { ... on Blob { text } }
+                  }
+                }
+            "#,
+            "variables": { "owner": owner, "name": repo },
+        });
 
-        let Some(encoded_content) = res.content else {
-            // No README found
-            return Ok(None);
+        let response: SingleReadmeResponse = self.inner.graphql(&body).await?;
+        let text = response
+            .data
+            .and_then(|d| d.repository)
+            .and_then(|r| r.object)
+            .and_then(|o| o.text);
+
+        self.readme_cache
+            .lock()
+            .expect("readme cache poisoned")
+            .insert(key, text.clone());
+        Ok(text)
+    }
+}
+
+/// Extract the `rel="next"` target from a `Link` header, if present.
+fn next_link(headers: &http::HeaderMap) -> Option<String> {
+    let link = headers.get(http::header::LINK)?.to_str().ok()?;
+    for part in link.split(',') {
+        let mut segments = part.split(';');
+        let Some(url) = segments.next() else {
+            continue;
         };
-        let cleaned = encoded_content.replace("\n", "");
-
-        let decoded_content =
-            BASE64_STANDARD
-                .decode(&cleaned)
-                .map_err(|err| OctocrabError::Serde {
-                    source: serde_json::Error::io(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Base64 decode error: {err}"),
-                    )),
-                    backtrace: std::backtrace::Backtrace::capture(),
-                })?;
+        if segments.any(|s| s.trim() == r#"rel="next""#) {
+            let trimmed = url.trim().trim_start_matches('<').trim_end_matches('>');
+            return Some(trimmed.to_string());
+        }
+    }
+    None
+}
 
-        let content = String::from_utf8(decoded_content).map_err(|e| OctocrabError::Serde {
-            source: serde_json::Error::io(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("UTF-8 error: {e}"),
-            )),
-            backtrace: std::backtrace::Backtrace::capture(),
-        })?;
+/// Deterministic per-route jitter so concurrent retries don't resynchronize.
+fn jitter_seed(path: &str, attempt: u32) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut hasher);
+    attempt.hash(&mut hasher);
+    hasher.finish()
+}
 
-        Ok(Some(content))
+/// Build an octocrab error from a GraphQL error message.
+fn graphql_error(message: String) -> OctocrabError {
+    OctocrabError::Serde {
+        source: serde_json::Error::io(std::io::Error::other(format!(
+            "GitHub GraphQL error: {message}"
+        ))),
+        backtrace: std::backtrace::Backtrace::capture(),
     }
 }
 
-/// Parses a URL and extracts the 'page' query parameter.
-pub fn get_page_from_url(url_str: &str) -> Option<u32> {
-    Url::parse(url_str)
-        .ok()?
-        .query_pairs()
-        .find_map(|(key, value)| {
-            if key == "page" {
-                value.parse::<u32>().ok()
-            } else {
-                None
-            }
+#[derive(Debug, Deserialize)]
+struct GraphQLResponse {
+    data: Option<StarredData>,
+    errors: Option<Vec<GraphQLError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StarredData {
+    viewer: Viewer,
+}
+
+#[derive(Debug, Deserialize)]
+struct Viewer {
+    #[serde(rename = "starredRepositories")]
+    starred_repositories: StarredConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct StarredConnection {
+    #[serde(rename = "pageInfo")]
+    page_info: PageInfo,
+    nodes: Vec<StarredNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageInfo {
+    #[serde(rename = "endCursor")]
+    end_cursor: Option<String>,
+    #[serde(rename = "hasNextPage")]
+    has_next_page: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct StarredNode {
+    #[serde(rename = "databaseId")]
+    database_id: Option<u64>,
+    #[serde(rename = "nameWithOwner")]
+    name_with_owner: String,
+    description: Option<String>,
+    url: String,
+    #[serde(rename = "stargazerCount")]
+    stargazer_count: Option<u64>,
+    #[serde(rename = "createdAt")]
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "updatedAt")]
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "primaryLanguage")]
+    primary_language: Option<Language>,
+    #[serde(rename = "repositoryTopics")]
+    repository_topics: Option<RepositoryTopics>,
+    object: Option<Blob>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Language {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryTopics {
+    nodes: Vec<TopicNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopicNode {
+    topic: Topic,
+}
+
+#[derive(Debug, Deserialize)]
+struct Topic {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Blob {
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SingleReadmeResponse {
+    data: Option<SingleRepositoryData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SingleRepositoryData {
+    repository: Option<RepositoryObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryObject {
+    object: Option<Blob>,
+}
+
+impl StarredNode {
+    /// Map a GraphQL node into the `octocrab::models::Repository` shape by
+    /// rebuilding the REST JSON representation octocrab deserializes from.
+    fn into_repository(self) -> Result<Repository, OctocrabError> {
+        let (owner, name) = self
+            .name_with_owner
+            .split_once('/')
+            .map(|(o, n)| (o.to_string(), n.to_string()))
+            .unwrap_or_else(|| (String::new(), self.name_with_owner.clone()));
+
+        let topics: Vec<String> = self
+            .repository_topics
+            .map(|t| t.nodes.into_iter().map(|n| n.topic.name).collect())
+            .unwrap_or_default();
+
+        let value = json!({
+            "id": self.database_id.unwrap_or_default(),
+            "node_id": "",
+            "name": name,
+            "full_name": self.name_with_owner,
+            "owner": { "login": owner, "id": 0, "node_id": "" },
+            "description": self.description,
+            "html_url": self.url,
+            "topics": topics,
+            "language": self.primary_language.map(|l| l.name),
+            "stargazers_count": self.stargazer_count.unwrap_or_default(),
+            "created_at": self.created_at,
+            "updated_at": self.updated_at,
+        });
+
+        serde_json::from_value(value).map_err(|e| OctocrabError::Serde {
+            source: e,
+            backtrace: std::backtrace::Backtrace::capture(),
         })
+    }
 }
@@ -0,0 +1,79 @@
+// Rate-limit handling for the GitHub client.
+//
+// GitHub enforces a primary hourly limit (surfaced via `X-RateLimit-*`) and a
+// secondary abuse limit (a `403`/`429` carrying `Retry-After`). A long scan of a
+// high-star account burns through enough requests to hit both, so the client
+// pauses rather than erroring: it sleeps until the primary window resets and
+// backs off with jitter on secondary limits.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// How the client responds when a rate limit is reached.
+#[derive(Debug, Clone)]
+pub struct RateLimitPolicy {
+    /// Upper bound on how long to sleep waiting for the primary window to reset;
+    /// a reset further out than this surfaces as an error instead of stalling.
+    pub max_sleep: Duration,
+    /// Number of secondary-limit retries before giving up.
+    pub max_retries: u32,
+    /// Base delay for the exponential backoff applied to secondary limits.
+    pub base_backoff: Duration,
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        Self {
+            max_sleep: Duration::from_secs(15 * 60),
+            max_retries: 5,
+            base_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RateLimitPolicy {
+    /// Backoff delay for secondary-limit `attempt` (0-based): the larger of the
+    /// server's `Retry-After` and an exponentially growing base, plus jitter.
+    ///
+    /// `jitter_seed` varies the added jitter deterministically per call site so
+    /// concurrent retries don't resynchronize into a thundering herd.
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>, jitter_seed: u64) -> Duration {
+        let exp = self.base_backoff.saturating_mul(1u32 << attempt.min(16));
+        let floor = retry_after.unwrap_or(Duration::ZERO).max(exp);
+        let jitter = Duration::from_millis(jitter_seed % 1000);
+        (floor + jitter).min(self.max_sleep)
+    }
+}
+
+/// Primary rate-limit state parsed from response headers.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitInfo {
+    pub remaining: u64,
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimitInfo {
+    /// Parse `X-RateLimit-Remaining` and `X-RateLimit-Reset` (unix seconds),
+    /// returning `None` when either header is absent or malformed.
+    pub fn from_headers(headers: &http::HeaderMap) -> Option<Self> {
+        let remaining = header_u64(headers, "x-ratelimit-remaining")?;
+        let reset_unix = header_u64(headers, "x-ratelimit-reset")?;
+        let reset = DateTime::from_timestamp(reset_unix as i64, 0)?;
+        Some(Self { remaining, reset })
+    }
+
+    /// Duration to sleep until the window resets, or `None` if it already has.
+    pub fn sleep_until_reset(&self) -> Option<Duration> {
+        (self.reset - Utc::now()).to_std().ok()
+    }
+}
+
+/// Parse a `Retry-After` header, supporting the delta-seconds form.
+pub fn retry_after(headers: &http::HeaderMap) -> Option<Duration> {
+    header_u64(headers, "retry-after").map(Duration::from_secs)
+}
+
+fn header_u64(headers: &http::HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
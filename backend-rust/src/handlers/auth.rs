@@ -0,0 +1,200 @@
+// GitHub OAuth device-authorization-grant flow.
+//
+// The browser OAuth middleware expects a redirect-capable client, which rules
+// out CLI tools and CI jobs. The device flow closes that gap: the server asks
+// GitHub for a `user_code`/`device_code` pair, hands the user a short code and a
+// verification URL to enter in any browser, then polls the token endpoint on the
+// user's behalf until they authorize. On success it materializes the same
+// [`OAuthCacheObject`] the browser flow persists, so the normal extractors work
+// unchanged.
+
+use std::time::Duration;
+
+use axum::{extract::State, response::Response, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    http::{bad_request, internal_error, success},
+    types::OAuthCacheObject,
+    AppState,
+};
+
+/// GitHub's device-flow and user endpoints.
+const DEVICE_CODE_URL: &str = "https://github.com/login/device/code";
+const ACCESS_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const USER_URL: &str = "https://api.github.com/user";
+
+/// Scope requested for device-flow tokens: read-only access to public profile
+/// and the user's stars.
+const DEVICE_SCOPE: &str = "read:user";
+
+/// Codes returned to the caller so the user can complete authorization in a
+/// browser while the server polls.
+#[derive(Debug, Serialize)]
+pub struct DeviceFlowStart {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    pub interval: u64,
+}
+
+/// Raw device-code response from GitHub.
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    expires_in: u64,
+    interval: u64,
+}
+
+/// Raw token-poll response: either an access token or an error slug such as
+/// `authorization_pending` / `slow_down` / `expired_token`.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+}
+
+/// Begin a device flow: request a code pair from GitHub and return it for the
+/// user to enter at the verification URL. The caller then hits
+/// [`complete_device_flow_handler`] to exchange the device code for a token.
+pub async fn start_device_flow_handler(State(app_state): State<AppState>) -> Response {
+    let client = reqwest::Client::new();
+    let response = client
+        .post(DEVICE_CODE_URL)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&[
+            ("client_id", app_state.config.github_client_id.as_str()),
+            ("scope", DEVICE_SCOPE),
+        ])
+        .send()
+        .await;
+
+    let parsed: Result<DeviceCodeResponse, _> = match response {
+        Ok(resp) => resp.json().await,
+        Err(e) => return internal_error(format!("Failed to request device code: {e}")),
+    };
+
+    match parsed {
+        Ok(code) => success(DeviceFlowStart {
+            device_code: code.device_code,
+            user_code: code.user_code,
+            verification_uri: code.verification_uri,
+            expires_in: code.expires_in,
+            interval: code.interval,
+        }),
+        Err(e) => internal_error(format!("Malformed device code response: {e}")),
+    }
+}
+
+/// The caller-supplied device code to exchange once the user has authorized.
+#[derive(Debug, Deserialize)]
+pub struct CompleteDeviceFlow {
+    pub device_code: String,
+    /// Server-provided poll interval, in seconds.
+    pub interval: u64,
+    /// Server-provided code lifetime, in seconds.
+    pub expires_in: u64,
+}
+
+/// Poll GitHub for the device-flow token, persist the resulting OAuth record,
+/// and return the minted access token the normal extractors consume.
+///
+/// Honors GitHub's pacing contract: waits `interval` seconds between polls,
+/// widens the interval on `slow_down`, keeps waiting on `authorization_pending`,
+/// and gives up on `expired_token` or once the code's lifetime elapses.
+pub async fn complete_device_flow_handler(
+    State(app_state): State<AppState>,
+    Json(payload): Json<CompleteDeviceFlow>,
+) -> Response {
+    let client = reqwest::Client::new();
+    let mut interval = Duration::from_secs(payload.interval.max(1));
+    let deadline = payload.expires_in;
+    let mut waited = 0u64;
+
+    let token = loop {
+        if waited >= deadline {
+            return bad_request("Device code expired before authorization");
+        }
+        tokio::time::sleep(interval).await;
+        waited += interval.as_secs();
+
+        let response = client
+            .post(ACCESS_TOKEN_URL)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[
+                ("client_id", app_state.config.github_client_id.as_str()),
+                ("device_code", payload.device_code.as_str()),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ])
+            .send()
+            .await;
+
+        let parsed: TokenResponse = match response {
+            Ok(resp) => match resp.json().await {
+                Ok(body) => body,
+                Err(e) => return internal_error(format!("Malformed token response: {e}")),
+            },
+            Err(e) => return internal_error(format!("Failed to poll for token: {e}")),
+        };
+
+        if let Some(token) = parsed.access_token {
+            break token;
+        }
+
+        match parsed.error.as_deref() {
+            Some("authorization_pending") => continue,
+            // Back off by the 5s GitHub mandates on repeated `slow_down`.
+            Some("slow_down") => {
+                interval += Duration::from_secs(5);
+                continue;
+            }
+            Some("expired_token") => {
+                return bad_request("Device code expired before authorization");
+            }
+            Some(other) => return bad_request(format!("Device authorization failed: {other}")),
+            None => return internal_error("Token response missing both token and error"),
+        }
+    };
+
+    // Fetch the authenticated user and persist the cache record the browser flow
+    // would have written, so subsequent requests authenticate as normal.
+    let user: serde_json::Value = match client
+        .get(USER_URL)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .header(reqwest::header::USER_AGENT, "starscout")
+        .bearer_auth(&token)
+        .send()
+        .await
+    {
+        Ok(resp) => match resp.json().await {
+            Ok(body) => body,
+            Err(e) => return internal_error(format!("Malformed user response: {e}")),
+        },
+        Err(e) => return internal_error(format!("Failed to fetch user: {e}")),
+    };
+
+    let oauth = match OAuthCacheObject::from_github_user(&user) {
+        Ok(oauth) => oauth,
+        Err(e) => return internal_error(format!("Failed to build OAuth record: {e}")),
+    };
+
+    if let Err(e) = app_state.database.upsert_oauth_cache(&oauth).await {
+        return internal_error(format!("Failed to persist OAuth record: {e}"));
+    }
+
+    // Stash the GitHub token so the session middleware can later rehydrate a
+    // client for this user without another round-trip.
+    if let Err(e) = app_state
+        .database
+        .set_oauth_access_token(&oauth.user_id, &token)
+        .await
+    {
+        return internal_error(format!("Failed to persist GitHub credential: {e}"));
+    }
+
+    // Mint the stateless session pair the authenticated extractors consume.
+    crate::session::mint_session_pair(&app_state, &oauth.user_id, &oauth.github_username)
+}
@@ -1,12 +1,22 @@
 // Job endpoint handlers for tracking processing progress
 
-use axum::{extract::State, response::IntoResponse};
+use std::convert::Infallible;
+
+use axum::{
+    extract::State,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use futures::stream::{self, StreamExt};
 use serde_json::json;
 
 use crate::{
     app_state::AppState,
     extractors::AuthenticatedUser,
     http::{internal_error, success},
+    services::ProgressEvent,
 };
 
 /// GET /jobs/status - Get current job processing status for the authenticated user
@@ -22,12 +32,24 @@ pub async fn job_status_handler(
 
     // Get the latest job for this user
     match app_state.job_manager.get_latest_job(user_id).await {
-        Ok(Some(job)) => success(json!({
-            "job": job,
-            "is_running": is_running,
-            "user_id": user_id,
-            "total_active_jobs": app_state.job_manager.active_job_count()
-        })),
+        Ok(Some(job)) => {
+            // Surface ingestion-retry health so users can see how many repositories
+            // were recovered, are still pending a retry, or gave up permanently.
+            let retry = app_state
+                .database
+                .retry_stats(job.id)
+                .await
+                .unwrap_or_default();
+            success(json!({
+                "job": job,
+                "is_running": is_running,
+                "user_id": user_id,
+                "total_active_jobs": app_state.job_manager.active_job_count(),
+                "retried": retry.retried,
+                "pending_retry": retry.pending_retry,
+                "permanently_failed": retry.permanently_failed
+            }))
+        }
         Ok(None) => success(json!({
             "job": null,
             "is_running": is_running,
@@ -41,3 +63,77 @@ pub async fn job_status_handler(
         }
     }
 }
+
+/// GET /jobs/events - Stream the authenticated user's latest job progress as SSE.
+///
+/// Replays the last known state from the database on connect, then forwards
+/// live [`ProgressEvent`]s from the job manager's broadcast channel until the
+/// job reaches a terminal state. This removes the polling latency and load of
+/// repeatedly hitting `/jobs/status`.
+pub async fn job_events_handler(
+    State(app_state): State<AppState>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Response {
+    let user_id = user.id.0 as i64;
+
+    // Resolve the job to stream and its current state for the replay event.
+    let job = match app_state.job_manager.get_latest_job(user_id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return success(json!({ "message": "No jobs found for user" })),
+        Err(e) => {
+            tracing::error!("Failed to get latest job for user {}: {}", user_id, e);
+            return internal_error("Failed to get job status");
+        }
+    };
+    let job_id = job.id;
+
+    let replay = ProgressEvent {
+        job_id,
+        status: job.status.clone(),
+        total_repos: job.total_repos,
+        processed_repos: job.processed_repos,
+        failed_repos: job.failed_repos,
+        terminal: is_terminal(&job.status),
+    };
+
+    // Subscribe before streaming so no event published after the replay snapshot
+    // is missed.
+    let receiver = app_state.job_manager.subscribe_progress(job_id);
+
+    let live = stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                // Dropped by lag: skip missed events and keep going.
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                // Sender gone: the job ended and the channel was removed.
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    // Forward events up to and including the terminal one, then stop.
+    let events = stream::once(async { replay })
+        .chain(live)
+        .scan(false, |done, event| {
+            if *done {
+                return std::future::ready(None);
+            }
+            *done = event.terminal;
+            std::future::ready(Some(event))
+        })
+        .map(sse_event);
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Render a [`ProgressEvent`] as a named SSE event carrying a JSON payload.
+fn sse_event(event: ProgressEvent) -> Result<Event, Infallible> {
+    let data = serde_json::to_string(&event).unwrap_or_else(|_| "{}".to_string());
+    Ok(Event::default().event("progress").data(data))
+}
+
+/// Whether a job status string represents a terminal state.
+fn is_terminal(status: &str) -> bool {
+    matches!(status, "completed" | "failed")
+}
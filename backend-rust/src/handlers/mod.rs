@@ -1,3 +1,4 @@
+pub mod auth;
 pub mod health;
 pub mod jobs;
 pub mod search;
@@ -7,6 +8,7 @@ pub mod user_exists;
 
 // HTTP request handlers for Axum routes
 
+pub use auth::{complete_device_flow_handler, start_device_flow_handler};
 pub use health::health_handler;
 pub use settings::get_settings_handler;
 pub use user_exists::user_exists_handler;
@@ -6,17 +6,22 @@ use tracing::instrument;
 
 use crate::{
     app_state::AppState,
-    extractors::AuthenticatedContext,
+    extractors::{AuthenticatedContext, EligibleUser},
     http::{internal_error, success},
 };
 
 #[instrument(skip_all, fields(user = user.login))]
 pub async fn generate_embeddings_handler(
     State(app_state): State<AppState>,
-    AuthenticatedContext {
-        user,
-        github_client,
-    }: AuthenticatedContext,
+    // Only accounts clearing the configured eligibility thresholds may trigger a
+    // scan; ineligible callers are rejected with a `403` before any work starts.
+    EligibleUser {
+        context: AuthenticatedContext {
+            user,
+            github_client,
+        },
+        ..
+    }: EligibleUser,
     headers: HeaderMap,
 ) -> Response {
     // Check if user has more starred repos than the configured threshold and require API key
@@ -1,20 +1,61 @@
+use std::sync::Arc;
+
 use crate::app_state::AppState;
 use crate::config::AppConfig;
 use crate::db::{Database, init_pg_pool};
-use crate::embedding::OpenAIEmbeddingService;
-use crate::services::{JobManager, SemanticSearchManager};
+use crate::embedding::{EmbeddingProvider, OpenAIEmbeddingService};
+use crate::github::cache::{Cache, CacheTtls, FilesystemCache, ResourceCache};
+use crate::github::ratelimit::RateLimitPolicy;
+use crate::services::{JobManager, NoopNotifier, Notifier, SemanticSearchManager, WebhookNotifier};
+use crate::session::SessionManager;
 use anyhow::{Context, Result};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{
+    EnvFilter, Layer, fmt, layer::SubscriberExt, util::SubscriberInitExt,
+};
+
+/// Initialize tracing with configuration-driven formatting and optional file output.
+///
+/// Logs always go to the console. When `log_dir` is set, a daily-rolling,
+/// non-blocking file appender is layered alongside it. `log_format` selects the
+/// `pretty` or `json` formatter; in `json` mode every line is a single JSON object
+/// carrying the span fields attached via `#[instrument]`.
+///
+/// Returns the appender's [`WorkerGuard`] (when a file is configured) which the
+/// caller must hold for the process lifetime so buffered lines flush on shutdown.
+pub fn init_tracing(config: &AppConfig) -> Result<Option<WorkerGuard>> {
+    let env_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(config.log_level.clone()));
+
+    let json = config.log_format.eq_ignore_ascii_case("json");
+
+    let console_layer = if json {
+        fmt::layer().json().boxed()
+    } else {
+        fmt::layer().boxed()
+    };
+
+    let (file_layer, guard) = match &config.log_dir {
+        Some(dir) if !dir.is_empty() => {
+            let appender = tracing_appender::rolling::daily(dir, "starscout.log");
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = if json {
+                fmt::layer().json().with_writer(writer).with_ansi(false).boxed()
+            } else {
+                fmt::layer().with_writer(writer).with_ansi(false).boxed()
+            };
+            (Some(layer), Some(guard))
+        }
+        _ => (None, None),
+    };
 
-/// Initialize tracing with environment-based configuration
-pub fn init_tracing() -> Result<()> {
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
+        .with(env_filter)
+        .with(console_layer)
+        .with(file_layer)
         .init();
-    Ok(())
+
+    Ok(guard)
 }
 
 /// Initialize all application services (database, embedding service, etc.)
@@ -33,20 +74,148 @@ pub async fn init_services(config: &AppConfig) -> Result<AppState> {
     // Create Database abstraction
     let database = Database::new(db_pool);
 
+    // Apply pending migrations before serving, unless disabled in configuration.
+    // This removes the manual out-of-band schema setup that otherwise blocks
+    // first-run deployments.
+    if config.auto_migrate {
+        database
+            .run_migrations()
+            .await
+            .with_context(|| "Failed to apply database migrations")?;
+    } else {
+        tracing::warn!("Automatic migrations disabled (auto_migrate=false)");
+    }
+
     tracing::info!("Database initialized successfully");
 
-    // Initialize OpenAI embedding service
-    let embedding_service = OpenAIEmbeddingService::new();
+    // Select the embedding backend from configuration. Only the OpenAI-compatible
+    // backend is implemented today; a base-URL override lets local inference
+    // servers that speak the same `/v1/embeddings` API be plugged in.
+    let embedding_service: Arc<dyn EmbeddingProvider> = match config.ai_provider.as_str() {
+        "openai" | "" => Arc::new(
+            OpenAIEmbeddingService::new()
+                .with_model(config.ai_model_name.clone())
+                .with_dimensions(config.ai_embedding_vector_dimension as usize)
+                .with_base_url(config.ai_base_url.clone().unwrap_or_default()),
+        ),
+        other => {
+            anyhow::bail!("Unsupported embedding provider: {other}");
+        }
+    };
+
+    // Fail fast if the backend's vector width doesn't match the stored schema:
+    // the embedding column is fixed-width, so a mismatched backend would corrupt
+    // the index.
+    if let Some(stored) = database
+        .embedding_dimensions()
+        .await
+        .with_context(|| "Failed to read embedding column dimension")?
+    {
+        let configured = embedding_service.dimensions();
+        if stored as usize != configured {
+            anyhow::bail!(
+                "Embedding backend '{}' produces {}-dim vectors but the schema stores {}-dim; \
+                 refusing to start.",
+                embedding_service.model_id(),
+                configured,
+                stored
+            );
+        }
+        tracing::info!(
+            "Embedding backend '{}' matches stored {}-dim schema",
+            embedding_service.model_id(),
+            stored
+        );
+    }
+
+    // Select the notification sink based on configuration; absent a webhook URL
+    // notifications are dropped via the no-op sink.
+    let notifier: Arc<dyn Notifier> = match &config.notifier_webhook_url {
+        Some(url) if !url.is_empty() => Arc::new(WebhookNotifier::new(url.clone())),
+        _ => Arc::new(NoopNotifier),
+    };
 
-    let repo_manager = SemanticSearchManager::new(embedding_service.clone(), database.clone());
-    let job_manager = JobManager::new(repo_manager, database.clone());
+    let repo_manager = SemanticSearchManager::new(embedding_service.clone(), database.clone())
+        .with_slow_operation_threshold(std::time::Duration::from_millis(
+            config.slow_operation_threshold_ms,
+        ));
+    let job_manager = JobManager::new(repo_manager, database.clone(), Arc::clone(&notifier));
+
+    // Build the session-token signer from configuration. HMAC is the default;
+    // RSA is selected when asymmetric verification (e.g. for external services)
+    // is required.
+    let session_manager = match config.session_signing_algorithm.as_str() {
+        "HS256" | "" => SessionManager::hmac(
+            config.session_hmac_secret.as_bytes(),
+            config.session_access_token_ttl_seconds,
+            config.session_refresh_token_ttl_seconds,
+        ),
+        "RS256" => {
+            let private = config
+                .session_rsa_private_key_pem
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("session_rsa_private_key_pem required for RS256"))?;
+            let public = config
+                .session_rsa_public_key_pem
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("session_rsa_public_key_pem required for RS256"))?;
+            SessionManager::rsa(
+                private.as_bytes(),
+                public.as_bytes(),
+                config.session_access_token_ttl_seconds,
+                config.session_refresh_token_ttl_seconds,
+            )
+            .with_context(|| "Failed to load RSA session keys")?
+        }
+        other => anyhow::bail!("Unsupported session signing algorithm: {other}"),
+    };
 
     tracing::info!("OpenAI embedding service initialized successfully");
 
+    let github_cache = build_github_cache(config);
+
+    // Carry the configurable rate-limit knobs into the policy every client uses,
+    // keeping the exponential base from the default.
+    let github_rate_limit_policy = RateLimitPolicy {
+        max_sleep: std::time::Duration::from_secs(config.github_rate_limit_max_sleep_seconds),
+        max_retries: config.github_rate_limit_max_retries,
+        ..RateLimitPolicy::default()
+    };
+
     Ok(AppState {
         database,
         embedding_service,
         config: config.clone(),
         job_manager,
+        notifier,
+        session_manager,
+        github_cache,
+        github_rate_limit_policy,
     })
 }
+
+/// Build the conditional-request cache for GitHub clients from configuration.
+///
+/// Returns `None` when caching is disabled. The Redis backend has no connection
+/// to use here, so it degrades to disabled with a warning rather than failing
+/// startup.
+fn build_github_cache(config: &AppConfig) -> Option<ResourceCache> {
+    let backend: Arc<dyn Cache> = match config.github_cache_backend.as_str() {
+        "filesystem" => Arc::new(FilesystemCache::new(&config.github_cache_dir)),
+        "redis" => {
+            tracing::warn!(
+                "Redis GitHub cache backend has no connection to reuse; caching disabled"
+            );
+            return None;
+        }
+        _ => return None,
+    };
+    let ttls = CacheTtls::new(chrono::Duration::seconds(
+        config.github_cache_ttl_seconds as i64,
+    ));
+    tracing::info!(
+        "GitHub response cache enabled (backend={})",
+        config.github_cache_backend
+    );
+    Some(ResourceCache::new(backend, ttls))
+}
@@ -2,12 +2,16 @@
 
 use dashmap::DashMap;
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
 use crate::db::Database;
 use crate::github::GitHubClient;
+use crate::services::notifier::{JobEvent, Notifier};
 use crate::services::{SemanticSearchManager, SemanticSearchManagerError};
 use crate::types::UserJob;
 use crate::types::repository::Repository;
@@ -44,48 +48,285 @@ pub enum JobError {
     TaskJoinError(#[from] tokio::task::JoinError),
 }
 
+impl JobError {
+    /// Whether the failure is transient and the job should be re-enqueued with
+    /// backoff rather than failed outright.
+    ///
+    /// Rate limits and 5xx responses from OpenAI and transient GitHub errors
+    /// are retryable; validation and auth failures are not.
+    pub fn retryable(&self) -> bool {
+        match self {
+            JobError::GitHubError(e) => is_retryable_github_error(e),
+            JobError::SemanticSearchManagerError(
+                SemanticSearchManagerError::EmbeddingError(e),
+            ) => is_retryable_embedding_error(e),
+            JobError::SemanticSearchManagerError(SemanticSearchManagerError::GitHubError(e)) => {
+                is_retryable_github_error(e)
+            }
+            JobError::DatabaseError(_) | JobError::TaskJoinError(_) => true,
+            JobError::SemanticSearchManagerError(_)
+            | JobError::JobAlreadyRunning { .. }
+            | JobError::JobNotFound { .. } => false,
+        }
+    }
+}
+
+/// Classify an OpenAI embedding error: rate limits and server errors are
+/// transient, everything else (bad input, auth) is permanent.
+fn is_retryable_embedding_error(err: &crate::embedding::EmbeddingError) -> bool {
+    use crate::embedding::EmbeddingError;
+    match err {
+        EmbeddingError::ApiError(e) => is_retryable_openai_error(e),
+        EmbeddingError::ConfigError(_) | EmbeddingError::ValidationError(_) => false,
+    }
+}
+
+/// OpenAI rate-limit (429) and 5xx responses are worth retrying.
+fn is_retryable_openai_error(err: &async_openai::error::OpenAIError) -> bool {
+    use async_openai::error::OpenAIError;
+    match err {
+        OpenAIError::ApiError(e) => {
+            matches!(
+                e.r#type.as_deref(),
+                Some("server_error" | "rate_limit_exceeded" | "requests" | "tokens")
+            ) || e.code.as_deref() == Some("rate_limit_exceeded")
+        }
+        // Transport failures and unparseable (e.g. HTML 5xx) bodies are transient.
+        OpenAIError::Reqwest(_) | OpenAIError::JSONDeserialize(_) => true,
+        _ => false,
+    }
+}
+
+/// Treat GitHub transport errors and 5xx/429 responses as transient.
+fn is_retryable_github_error(err: &octocrab::Error) -> bool {
+    match err {
+        octocrab::Error::GitHub { source, .. } => {
+            let status = source.status_code.as_u16();
+            status == 429 || (500..600).contains(&status)
+        }
+        octocrab::Error::Http { .. } | octocrab::Error::Hyper { .. } => true,
+        _ => false,
+    }
+}
+
+/// A live progress update for a job, published to subscribers of its
+/// [`JobManager`] broadcast channel and rendered as SSE.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProgressEvent {
+    pub job_id: i32,
+    pub status: String,
+    pub total_repos: i32,
+    pub processed_repos: i32,
+    pub failed_repos: i32,
+    /// Set on the last event the job's task publishes (carrying the job's final
+    /// DB status: completed, failed, or pending when requeued for a retry) so
+    /// the SSE stream can close.
+    pub terminal: bool,
+}
+
+/// A job spawned by this process, either waiting on a worker permit (`queued`)
+/// or actively running.
+#[derive(Debug)]
+struct ActiveJob {
+    job_id: i32,
+    handle: JoinHandle<()>,
+    /// Flipped to `true` once the task acquires a global worker permit and
+    /// starts real work; until then the job is merely queued.
+    running: Arc<std::sync::atomic::AtomicBool>,
+}
+
 /// JobManager handles asynchronous processing of user starred repositories
 /// It tracks active jobs and manages background tasks for generating embeddings
 #[derive(Debug, Clone)]
 pub struct JobManager {
     repo_manager: SemanticSearchManager,
     database: Database,
-    active_jobs: Arc<DashMap<i64, (i32, JoinHandle<()>)>>, // (user_id, (job_id, handle))
+    notifier: Arc<dyn Notifier>,
+    active_jobs: Arc<DashMap<i64, ActiveJob>>, // user_id -> spawned job
+    /// Caps the number of jobs doing real GitHub/OpenAI work at once; jobs
+    /// beyond the limit wait here in a `queued` state.
+    worker_slots: Arc<tokio::sync::Semaphore>,
+    /// Per-job progress broadcast channels, so SSE subscribers receive live
+    /// updates without polling. Keyed by job id; entries are removed when the
+    /// job ends.
+    progress_channels: Arc<DashMap<i32, tokio::sync::broadcast::Sender<ProgressEvent>>>,
+    /// Identifies this process when claiming jobs, so a crashed instance's jobs
+    /// can be told apart from live ones during recovery.
+    worker_id: Uuid,
 }
 
 impl JobManager {
+    /// A running job whose heartbeat is older than this is assumed to belong to
+    /// a crashed worker and is requeued (not failed) on startup.
+    const STALE_JOB_TIMEOUT: Duration = Duration::from_secs(300);
+
+    /// How often a running job bumps its heartbeat so live work is not mistaken
+    /// for a crashed worker's.
+    const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+    /// Default number of jobs allowed to do real work concurrently.
+    const DEFAULT_WORKER_LIMIT: usize = 4;
+
     /// Create a new JobManager instance
-    pub fn new(repo_manager: SemanticSearchManager, database: Database) -> Self {
+    pub fn new(
+        repo_manager: SemanticSearchManager,
+        database: Database,
+        notifier: Arc<dyn Notifier>,
+    ) -> Self {
+        Self::with_worker_limit(repo_manager, database, notifier, Self::DEFAULT_WORKER_LIMIT)
+    }
+
+    /// Create a JobManager with a custom global worker limit.
+    pub fn with_worker_limit(
+        repo_manager: SemanticSearchManager,
+        database: Database,
+        notifier: Arc<dyn Notifier>,
+        worker_limit: usize,
+    ) -> Self {
         Self {
             repo_manager,
             database,
+            notifier,
             active_jobs: Arc::new(DashMap::new()),
+            worker_slots: Arc::new(tokio::sync::Semaphore::new(worker_limit.max(1))),
+            progress_channels: Arc::new(DashMap::new()),
+            worker_id: Uuid::new_v4(),
         }
     }
 
-    /// Initialize the JobManager by cleaning up any stale jobs from previous server runs
-    /// This should be called once during server startup
-    pub async fn initialize(&self) -> Result<(), JobError> {
-        info!("Initializing JobManager and cleaning up stale jobs...");
+    /// Capacity of each per-job progress broadcast channel; bounds how many
+    /// unconsumed events a slow subscriber may lag behind before it is dropped.
+    const PROGRESS_CHANNEL_CAPACITY: usize = 64;
 
-        // Find all incomplete jobs (jobs that were running when server shut down)
-        let incomplete_jobs = self.database.get_incomplete_jobs().await?;
+    /// Subscribe to a job's live progress events.
+    ///
+    /// Creates the channel if the job has not emitted yet, so a subscriber that
+    /// connects before the first event still receives subsequent updates. The
+    /// SSE handler replays the last known DB state separately on connect.
+    pub fn subscribe_progress(
+        &self,
+        job_id: i32,
+    ) -> tokio::sync::broadcast::Receiver<ProgressEvent> {
+        self.progress_channels
+            .entry(job_id)
+            .or_insert_with(|| {
+                tokio::sync::broadcast::channel(Self::PROGRESS_CHANNEL_CAPACITY).0
+            })
+            .subscribe()
+    }
 
-        if !incomplete_jobs.is_empty() {
-            let job_ids: Vec<i32> = incomplete_jobs.iter().map(|job| job.id.unwrap()).collect();
-            info!(
-                "Found {} stale jobs, marking them as failed: {:?}",
-                incomplete_jobs.len(),
-                job_ids
-            );
+    /// Publish a progress event to a job's subscribers, if any. A send error
+    /// just means nobody is currently listening, which is fine.
+    fn publish_progress(
+        channels: &DashMap<i32, tokio::sync::broadcast::Sender<ProgressEvent>>,
+        event: ProgressEvent,
+    ) {
+        if let Some(sender) = channels.get(&event.job_id) {
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Fire a terminal notification for `job_id`, loading its final state so the
+    /// event carries the processed/failed counts. Notification failures are
+    /// swallowed so they never affect the job outcome.
+    async fn notify_terminal(notifier: &Arc<dyn Notifier>, database: &Database, job_id: i32) {
+        match database.get_job(job_id).await {
+            Ok(Some(job)) => {
+                let event = JobEvent {
+                    user_id: job.user_id.to_i64().unwrap_or_default(),
+                    job_id,
+                    status: job.status.clone(),
+                    processed_repos: job.processed_repos,
+                    failed_repos: job.failed_repos,
+                    success_rate: job.success_rate(),
+                };
+                notifier.notify(event).await;
+            }
+            Ok(None) => warn!("Cannot notify for missing job {}", job_id),
+            Err(e) => warn!("Failed to load job {} for notification: {}", job_id, e),
+        }
+    }
 
-            // Mark all stale jobs as failed since they were interrupted
-            self.database.fail_jobs(&job_ids).await?;
+    /// Base backoff for a job-level retry; the delay is `base * 2^(attempt-1)`,
+    /// capped at [`Self::MAX_RETRY_BACKOFF`] with jitter applied.
+    const RETRY_BASE_BACKOFF: Duration = Duration::from_secs(30);
+
+    /// Upper bound on the retry backoff regardless of attempt count.
+    const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(3600);
+
+    /// Decide a failed job's fate: re-enqueue it with exponential backoff when
+    /// the error is transient and retries remain, otherwise fail it terminally.
+    ///
+    /// A rescheduled job is left `pending` for [`Database::claim_next_job`];
+    /// this manager does not claim or re-run pending jobs itself. When a
+    /// rescheduled job is run again, [`SemanticSearchManager`]'s
+    /// already-embedded skip logic means repositories whose embeddings already
+    /// succeeded are not redone.
+    async fn finalize_failure(
+        database: &Database,
+        notifier: &Arc<dyn Notifier>,
+        job_id: i32,
+        error: &JobError,
+    ) {
+        if error.retryable() {
+            match database.job_retry_budget(job_id).await {
+                Ok(Some((attempts, max_attempts))) if attempts + 1 < max_attempts => {
+                    let next_attempt = attempts + 1;
+                    let not_before = chrono::Utc::now() + Self::retry_backoff(next_attempt);
+                    match database
+                        .reschedule_job(job_id, next_attempt, not_before)
+                        .await
+                    {
+                        Ok(_) => {
+                            info!(
+                                "Requeued job {} for retry {}/{} at {}",
+                                job_id, next_attempt, max_attempts, not_before
+                            );
+                            return;
+                        }
+                        Err(e) => warn!("Failed to reschedule job {}: {}", job_id, e),
+                    }
+                }
+                Ok(_) => info!("Job {} exhausted its retries; failing", job_id),
+                Err(e) => warn!("Could not read retry budget for job {}: {}", job_id, e),
+            }
+        }
 
-            info!(
-                "Successfully cleaned up {} stale jobs",
-                incomplete_jobs.len()
-            );
+        if let Err(e) = database.fail_job(job_id).await {
+            warn!("Failed to mark job {} as failed: {}", job_id, e);
+        }
+        Self::notify_terminal(notifier, database, job_id).await;
+    }
+
+    /// Compute the backoff for `attempt`: `base * 2^(attempt-1)`, capped, with
+    /// up to ±25% jitter to avoid a thundering herd of synchronized retries.
+    fn retry_backoff(attempt: i32) -> chrono::Duration {
+        let exp = attempt.saturating_sub(1).min(16) as u32;
+        let base = Self::RETRY_BASE_BACKOFF.as_secs_f64() * 2f64.powi(exp as i32);
+        let capped = base.min(Self::MAX_RETRY_BACKOFF.as_secs_f64());
+        // Deterministic jitter derived from the job's attempt, in [0.75, 1.25).
+        let jitter = 0.75 + ((attempt as f64 * 0.37).fract()) * 0.5;
+        chrono::Duration::milliseconds((capped * jitter * 1000.0) as i64)
+    }
+
+    /// Initialize the JobManager by recovering jobs abandoned by crashed workers.
+    ///
+    /// Only running jobs whose heartbeat has gone stale are requeued back to
+    /// `pending` — viable jobs that a peer instance is still working on, and
+    /// jobs merely interrupted cleanly, are left untouched. This replaces the
+    /// old blanket-fail behaviour, which was wrong for still-viable jobs and
+    /// precluded running more than one server instance. This should be called
+    /// once during server startup.
+    pub async fn initialize(&self) -> Result<(), JobError> {
+        info!("Initializing JobManager and recovering stale jobs...");
+
+        let requeued = self
+            .database
+            .requeue_stale_jobs(Self::STALE_JOB_TIMEOUT)
+            .await?;
+
+        if requeued > 0 {
+            info!("Requeued {} stale job(s) from crashed workers", requeued);
         } else {
             info!("No stale jobs found during initialization");
         }
@@ -100,7 +341,6 @@ impl JobManager {
         user_id: i64,
         api_key: &str,
         github_client: &GitHubClient,
-        starred_repos_count: usize,
     ) -> Result<i32, JobError> {
         // Check if job is already running in memory
         if self.active_jobs.contains_key(&user_id) {
@@ -109,46 +349,123 @@ impl JobManager {
 
         info!("Starting background job for user: {}", user_id);
 
-        // Create job record in database
+        // Create the job record. It stays `queued` until a global worker permit
+        // is free, so a burst of users cannot spawn unbounded concurrent work.
         let job = self.database.create_job(user_id.into()).await?;
-        let job_id = job.id.unwrap(); // Safe because database returns the ID
+        let job_id = job.id;
+        self.database.update_job_status(job_id, "queued").await?;
 
         // Clone necessary data for the spawned task
         let repo_manager = self.repo_manager.clone();
         let database = self.database.clone();
+        let notifier = Arc::clone(&self.notifier);
         let active_jobs = Arc::clone(&self.active_jobs);
+        let worker_slots = Arc::clone(&self.worker_slots);
+        let worker_id = self.worker_id;
+        let running = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let task_running = Arc::clone(&running);
+
+        // Ensure the progress channel exists so subscribers connecting during the
+        // queued phase don't miss the first live event.
+        self.progress_channels.entry(job_id).or_insert_with(|| {
+            tokio::sync::broadcast::channel(Self::PROGRESS_CHANNEL_CAPACITY).0
+        });
+        let progress_channels = Arc::clone(&self.progress_channels);
 
         let api_key = api_key.to_string();
         let github_client = github_client.clone();
 
         // Spawn the background task
         let handle = tokio::spawn(async move {
+            // Wait for a worker permit; held for the job's lifetime so only
+            // `worker_limit` jobs do real work at once.
+            let _permit = worker_slots
+                .acquire()
+                .await
+                .expect("worker semaphore is never closed");
+            task_running.store(true, std::sync::atomic::Ordering::SeqCst);
+
+            // Now that a slot is ours, claim the job under this worker so its
+            // heartbeat can be tracked for crash recovery.
+            if let Err(e) = database.begin_job(job_id, worker_id).await {
+                error!("Failed to start queued job {}: {}", job_id, e);
+                active_jobs.remove(&user_id);
+                return;
+            }
+
+            // Periodically bump the heartbeat so this live job isn't mistaken for
+            // a crashed worker's and requeued out from under us.
+            let heartbeat = tokio::spawn({
+                let database = database.clone();
+                async move {
+                    let mut ticker = tokio::time::interval(Self::HEARTBEAT_INTERVAL);
+                    loop {
+                        ticker.tick().await;
+                        match database.heartbeat_job(job_id, worker_id).await {
+                            Ok(true) => {}
+                            // The row was reclaimed or finished; stop heartbeating.
+                            Ok(false) => break,
+                            Err(e) => warn!("Heartbeat failed for job {}: {}", job_id, e),
+                        }
+                    }
+                }
+            });
+
             let result = Self::process_user_stars(
                 user_id,
                 job_id,
                 repo_manager,
                 &github_client,
-                database,
+                database.clone(),
+                Arc::clone(&notifier),
+                Arc::clone(&progress_channels),
                 api_key,
-                starred_repos_count,
             )
             .await;
 
+            heartbeat.abort();
+
             match result {
                 Ok(_) => {
                     info!("Successfully completed job for user: {}", user_id);
                 }
                 Err(e) => {
                     error!("Job failed for user {}: {:?}", user_id, e);
+                    Self::finalize_failure(&database, &notifier, job_id, &e).await;
                 }
             }
 
+            // Emit a terminal progress event reflecting the final DB state, then
+            // drop the channel so subscribers close their streams.
+            if let Ok(Some(job)) = database.get_job(job_id).await {
+                Self::publish_progress(
+                    &progress_channels,
+                    ProgressEvent {
+                        job_id,
+                        status: job.status,
+                        total_repos: job.total_repos,
+                        processed_repos: job.processed_repos,
+                        failed_repos: job.failed_repos,
+                        terminal: true,
+                    },
+                );
+            }
+            progress_channels.remove(&job_id);
+
             // Remove job from active jobs when complete
             active_jobs.remove(&user_id);
         });
 
-        // Store the job ID and handle
-        self.active_jobs.insert(user_id, (job_id, handle));
+        // Track the spawned job (queued until its permit is acquired) and return
+        // its id immediately regardless of whether it started or is waiting.
+        self.active_jobs.insert(
+            user_id,
+            ActiveJob {
+                job_id,
+                handle,
+                running,
+            },
+        );
 
         Ok(job_id)
     }
@@ -160,18 +477,30 @@ impl JobManager {
         repo_manager: SemanticSearchManager,
         github_client: &GitHubClient,
         database: Database,
+        notifier: Arc<dyn Notifier>,
+        progress_channels: Arc<DashMap<i32, tokio::sync::broadcast::Sender<ProgressEvent>>>,
         api_key: String,
-        starred_repos_count: usize,
     ) -> Result<(), JobError> {
         info!("Processing starred repositories for user: {}", user_id);
 
-        // Update job status to fetching stars
+        // Publish the fetching-stars phase as a human-readable progress message
         database
-            .update_job_status(job_id, "Fetching stars...")
+            .update_job_progress_message(job_id, "Fetching stars...")
             .await?;
+        Self::publish_progress(
+            &progress_channels,
+            ProgressEvent {
+                job_id,
+                status: "Fetching stars...".to_string(),
+                total_repos: 0,
+                processed_repos: 0,
+                failed_repos: 0,
+                terminal: false,
+            },
+        );
 
         // Fetch starred repositories via GitHub client
-        let octo_repos = github_client.get_starred_repos(starred_repos_count).await?;
+        let octo_repos = github_client.get_starred_repos().await?;
         let starred_repos: Vec<Repository> = octo_repos
             .into_iter()
             .map(Repository::from_octocrab)
@@ -187,8 +516,19 @@ impl JobManager {
             .update_job_progress(job_id, starred_repos.len() as i32, 0, 0)
             .await?;
         database
-            .update_job_status(job_id, "Creating embeddings...")
+            .update_job_progress_message(job_id, "Creating embeddings...")
             .await?;
+        Self::publish_progress(
+            &progress_channels,
+            ProgressEvent {
+                job_id,
+                status: "Creating embeddings...".to_string(),
+                total_repos: starred_repos.len() as i32,
+                processed_repos: 0,
+                failed_repos: 0,
+                terminal: false,
+            },
+        );
 
         // Generate and store embeddings using RepoManager
         // Progress is now updated incrementally inside generate_and_store_embeddings
@@ -210,7 +550,8 @@ impl JobManager {
                 );
             }
             Err(e) => {
-                // Mark all as failed and propagate error
+                // Record the failed repositories and propagate; the caller
+                // decides whether to retry with backoff or fail the job.
                 database
                     .update_job_progress(
                         job_id,
@@ -219,7 +560,6 @@ impl JobManager {
                         starred_repos.len() as i32,
                     )
                     .await?;
-                database.fail_job(job_id).await?;
                 return Err(e.into());
             }
         }
@@ -246,19 +586,25 @@ impl JobManager {
 
         // Mark job as completed
         database.complete_job(job_id).await?;
+        Self::notify_terminal(&notifier, &database, job_id).await;
 
         Ok(())
     }
 
-    /// List all currently active job user IDs
-    pub fn list_active_jobs(&self) -> Vec<i64> {
-        self.active_jobs.iter().map(|entry| *entry.key()).collect()
+    /// List all spawned jobs as `(user_id, is_running)` pairs, so callers can
+    /// tell running jobs apart from those still queued for a worker permit.
+    pub fn list_active_jobs(&self) -> Vec<(i64, bool)> {
+        use std::sync::atomic::Ordering;
+        self.active_jobs
+            .iter()
+            .map(|entry| (*entry.key(), entry.running.load(Ordering::SeqCst)))
+            .collect()
     }
 
     /// Stop a running job for a specific user
     /// Returns an error if no job is found for the user
     pub async fn stop_job(&self, user_id: i64) -> Result<(), JobError> {
-        if let Some((_, (job_id, handle))) = self.active_jobs.remove(&user_id) {
+        if let Some((_, ActiveJob { job_id, handle, .. })) = self.active_jobs.remove(&user_id) {
             info!("Stopping job for user: {} (job_id: {})", user_id, job_id);
             handle.abort();
 
@@ -292,9 +638,22 @@ impl JobManager {
         self.active_jobs.contains_key(&user_id)
     }
 
-    /// Get the number of currently active jobs
+    /// Get the number of jobs currently doing real work (permit acquired).
     pub fn active_job_count(&self) -> usize {
-        self.active_jobs.len()
+        use std::sync::atomic::Ordering;
+        self.active_jobs
+            .iter()
+            .filter(|entry| entry.running.load(Ordering::SeqCst))
+            .count()
+    }
+
+    /// Get the number of jobs spawned but still waiting on a worker permit.
+    pub fn queued_job_count(&self) -> usize {
+        use std::sync::atomic::Ordering;
+        self.active_jobs
+            .iter()
+            .filter(|entry| !entry.running.load(Ordering::SeqCst))
+            .count()
     }
 
     /// Get job by ID
@@ -1,7 +1,9 @@
 pub mod job_manager;
+pub mod notifier;
 pub mod semantic_search_manager;
 
-pub use job_manager::{JobError, JobManager};
+pub use job_manager::{JobError, JobManager, ProgressEvent};
+pub use notifier::{JobEvent, NoopNotifier, Notifier, WebhookNotifier};
 pub use semantic_search_manager::{SemanticSearchManager, SemanticSearchManagerError};
 
 // Core business logic services
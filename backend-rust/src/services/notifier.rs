@@ -0,0 +1,88 @@
+// Job-completion notifications with pluggable sinks
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tracing::{error, warn};
+
+/// A terminal job event emitted when a job transitions into a completed or
+/// failed state.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobEvent {
+    pub user_id: i64,
+    pub job_id: i32,
+    pub status: String,
+    pub processed_repos: i32,
+    pub failed_repos: i32,
+    pub success_rate: f64,
+}
+
+/// A sink that is notified when a job reaches a terminal state.
+///
+/// Implementations must never propagate errors back to the caller: a broken
+/// notification sink should never block or fail a job. They are expected to
+/// log-and-drop on final failure.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, event: JobEvent);
+}
+
+/// A [`Notifier`] that POSTs each event as JSON to a configured webhook URL,
+/// retrying transient failures with exponential backoff.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    /// Maximum number of delivery attempts before the event is dropped.
+    const MAX_ATTEMPTS: u32 = 3;
+
+    /// Create a new webhook notifier targeting `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, event: JobEvent) {
+        for attempt in 0..Self::MAX_ATTEMPTS {
+            match self.client.post(&self.url).json(&event).send().await {
+                Ok(resp) if resp.status().is_success() => return,
+                Ok(resp) => warn!(
+                    "Webhook notification for job {} returned status {}",
+                    event.job_id,
+                    resp.status()
+                ),
+                Err(e) => warn!("Webhook notification for job {} failed: {}", event.job_id, e),
+            }
+
+            // Back off 1s, then 2s, between the three attempts; the last attempt does not sleep.
+            if attempt + 1 < Self::MAX_ATTEMPTS {
+                let backoff = Duration::from_secs(1 << attempt);
+                tokio::time::sleep(backoff).await;
+            }
+        }
+
+        error!(
+            "Dropping webhook notification for job {} after {} attempts",
+            event.job_id,
+            Self::MAX_ATTEMPTS
+        );
+    }
+}
+
+/// A no-op notifier used when no webhook URL is configured.
+#[derive(Debug, Clone, Default)]
+pub struct NoopNotifier;
+
+#[async_trait]
+impl Notifier for NoopNotifier {
+    async fn notify(&self, _event: JobEvent) {}
+}
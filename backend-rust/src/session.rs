@@ -0,0 +1,285 @@
+// Stateless signed session tokens.
+//
+// The authenticated extractors read an `Author` and `GitHubClient` out of the
+// request extensions but say nothing about how they get there. This module
+// defines the token format that backs them: a JWT signed with either an HMAC
+// secret or an RSA key pair (selected by configuration), carrying the GitHub
+// user id, username, and an expiry. [`session_auth_middleware`] validates the
+// bearer token, rehydrates the `Author`/`GitHubClient` from the per-user GitHub
+// token in the OAuth cache, and injects them so the existing extractors keep
+// working.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header::AUTHORIZATION, Request},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    eligibility::{EligibilityRules, StarCount},
+    github::GitHubClient,
+    http::{internal_error, unauthorized},
+    AppState,
+};
+
+/// Token kind, embedded in the claims so a refresh token can't be replayed as an
+/// access token and vice versa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    Access,
+    Refresh,
+}
+
+/// Claims carried by a session token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// GitHub user id.
+    pub sub: String,
+    /// GitHub username.
+    pub username: String,
+    /// Expiry, as a Unix timestamp.
+    pub exp: usize,
+    /// Unique token id, used as the revocation key.
+    pub jti: String,
+    /// Whether this is an access or refresh token.
+    pub kind: TokenKind,
+}
+
+/// Signs and verifies session tokens using the configured algorithm.
+#[derive(Clone)]
+pub struct SessionManager {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    algorithm: Algorithm,
+    access_ttl: i64,
+    refresh_ttl: i64,
+}
+
+impl std::fmt::Debug for SessionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionManager")
+            .field("algorithm", &self.algorithm)
+            .field("access_ttl", &self.access_ttl)
+            .field("refresh_ttl", &self.refresh_ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionManager {
+    /// Build an HMAC-signed (`HS256`) session manager.
+    pub fn hmac(secret: &[u8], access_ttl: i64, refresh_ttl: i64) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+            algorithm: Algorithm::HS256,
+            access_ttl,
+            refresh_ttl,
+        }
+    }
+
+    /// Build an RSA-signed (`RS256`) session manager from PEM key material.
+    pub fn rsa(
+        private_pem: &[u8],
+        public_pem: &[u8],
+        access_ttl: i64,
+        refresh_ttl: i64,
+    ) -> Result<Self, jsonwebtoken::errors::Error> {
+        Ok(Self {
+            encoding_key: EncodingKey::from_rsa_pem(private_pem)?,
+            decoding_key: DecodingKey::from_rsa_pem(public_pem)?,
+            algorithm: Algorithm::RS256,
+            access_ttl,
+            refresh_ttl,
+        })
+    }
+
+    /// Mint a token of the given kind for a user, returning `(token, jti)`.
+    pub fn mint(
+        &self,
+        kind: TokenKind,
+        user_id: &str,
+        username: &str,
+    ) -> Result<(String, String), jsonwebtoken::errors::Error> {
+        let ttl = match kind {
+            TokenKind::Access => self.access_ttl,
+            TokenKind::Refresh => self.refresh_ttl,
+        };
+        let jti = Uuid::new_v4().to_string();
+        let exp = (chrono::Utc::now() + chrono::Duration::seconds(ttl)).timestamp() as usize;
+        let claims = Claims {
+            sub: user_id.to_string(),
+            username: username.to_string(),
+            exp,
+            jti: jti.clone(),
+            kind,
+        };
+        let token = jsonwebtoken::encode(&Header::new(self.algorithm), &claims, &self.encoding_key)?;
+        Ok((token, jti))
+    }
+
+    /// Verify a token's signature and expiry, returning its claims.
+    pub fn validate(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        let validation = Validation::new(self.algorithm);
+        let data = jsonwebtoken::decode::<Claims>(token, &self.decoding_key, &validation)?;
+        Ok(data.claims)
+    }
+}
+
+/// Pull the bearer token out of the `Authorization` header.
+fn bearer_token(req: &Request<Body>) -> Option<&str> {
+    req.headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Validate the session token, rehydrate the `Author`/`GitHubClient`, and insert
+/// them into request extensions for the downstream extractors.
+pub async fn session_auth_middleware(
+    State(app_state): State<AppState>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(token) = bearer_token(&req) else {
+        return unauthorized("Missing or malformed Authorization header");
+    };
+
+    let claims = match app_state.session_manager.validate(token) {
+        Ok(claims) => claims,
+        Err(_) => return unauthorized("Invalid or expired session token"),
+    };
+
+    if claims.kind != TokenKind::Access {
+        return unauthorized("Refresh tokens cannot be used to authenticate requests");
+    }
+
+    // A revoked jti (logout) invalidates an otherwise-valid token immediately.
+    match app_state.database.is_session_revoked(&claims.jti).await {
+        Ok(true) => return unauthorized("Session has been revoked"),
+        Ok(false) => {}
+        Err(e) => return internal_error(format!("Failed to check revocation: {e}")),
+    }
+
+    // Rehydrate the GitHub client from the per-user token stored at login.
+    let github_token = match app_state.database.get_oauth_access_token(&claims.sub).await {
+        Ok(Some(token)) => token,
+        Ok(None) => return unauthorized("No GitHub credential on file for this session"),
+        Err(e) => return internal_error(format!("Failed to load GitHub credential: {e}")),
+    };
+
+    let client = match GitHubClient::new(github_token) {
+        Ok(client) => {
+            let client =
+                client.with_rate_limit_policy(app_state.github_rate_limit_policy.clone());
+            match &app_state.github_cache {
+                Some(cache) => client.with_cache(cache.clone()),
+                None => client,
+            }
+        }
+        Err(_) => return internal_error("Failed to construct GitHub client"),
+    };
+
+    let user = match client.get_authenticated_user().await {
+        Ok(user) => user,
+        Err(_) => return unauthorized("Stored GitHub credential is no longer valid"),
+    };
+
+    req.extensions_mut().insert(user);
+    req.extensions_mut().insert(client);
+
+    // Supply the eligibility gate's inputs so the `EligibleUser` extractor can
+    // run on gated routes: the configured thresholds, the cached OAuth record,
+    // and the user's known star count.
+    req.extensions_mut().insert(EligibilityRules {
+        min_following: app_state.config.github_following_threshold as i32,
+        min_account_age: chrono::Duration::days(app_state.config.github_min_account_age_days),
+        min_stars: app_state.config.github_star_threshold as u32,
+    });
+    if let Ok(Some(oauth)) = app_state.database.get_oauth_cache(&claims.sub).await {
+        req.extensions_mut().insert(oauth);
+    }
+    if let Ok(user_id) = claims.sub.parse() {
+        if let Ok(ids) = app_state.database.get_user_starred_repo_ids(user_id).await {
+            req.extensions_mut().insert(StarCount(ids.len() as u32));
+        }
+    }
+
+    next.run(req).await
+}
+
+/// Body for the refresh endpoint.
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Rotate a refresh token: validate it, revoke its jti, and mint a fresh
+/// access/refresh pair. Rotation means a stolen refresh token is usable at most
+/// once before the legitimate client invalidates it.
+pub async fn refresh_handler(
+    State(app_state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Response {
+    let claims = match app_state.session_manager.validate(&payload.refresh_token) {
+        Ok(claims) if claims.kind == TokenKind::Refresh => claims,
+        Ok(_) => return unauthorized("Expected a refresh token"),
+        Err(_) => return unauthorized("Invalid or expired refresh token"),
+    };
+
+    match app_state.database.is_session_revoked(&claims.jti).await {
+        Ok(true) => return unauthorized("Refresh token has been revoked"),
+        Ok(false) => {}
+        Err(e) => return internal_error(format!("Failed to check revocation: {e}")),
+    }
+
+    if let Err(e) = app_state.database.revoke_session(&claims.jti).await {
+        return internal_error(format!("Failed to rotate refresh token: {e}"));
+    }
+
+    mint_session_pair(&app_state, &claims.sub, &claims.username)
+}
+
+/// Body for the logout endpoint.
+#[derive(Debug, Deserialize)]
+pub struct LogoutRequest {
+    pub jti: String,
+}
+
+/// Revoke a session token so it can no longer authenticate, even before expiry.
+pub async fn logout_handler(
+    State(app_state): State<AppState>,
+    Json(payload): Json<LogoutRequest>,
+) -> Response {
+    match app_state.database.revoke_session(&payload.jti).await {
+        Ok(()) => crate::http::success(json!({ "revoked": true })),
+        Err(e) => internal_error(format!("Failed to revoke session: {e}")),
+    }
+}
+
+/// Mint and return an access/refresh token pair for a user.
+pub fn mint_session_pair(app_state: &AppState, user_id: &str, username: &str) -> Response {
+    let access = app_state
+        .session_manager
+        .mint(TokenKind::Access, user_id, username);
+    let refresh = app_state
+        .session_manager
+        .mint(TokenKind::Refresh, user_id, username);
+
+    match (access, refresh) {
+        (Ok((access_token, _)), Ok((refresh_token, _))) => crate::http::success(json!({
+            "access_token": access_token,
+            "refresh_token": refresh_token,
+        })),
+        _ => internal_error("Failed to mint session tokens"),
+    }
+}
@@ -3,10 +3,12 @@ use serde::{Deserialize, Serialize};
 // Re-export all domain types
 pub mod oauth_cache;
 pub mod repository;
+pub mod saved_query;
 pub mod user_job;
 
 // Re-export structs for easy importing
 pub use oauth_cache::{OAuthCacheError, OAuthCacheObject};
+pub use saved_query::SavedQuery;
 pub use user_job::{UserJob, job_status};
 
 // Common types that might be shared across modules
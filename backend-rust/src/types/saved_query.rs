@@ -0,0 +1,17 @@
+// Saved semantic queries backing per-user discovery feeds.
+
+use rust_decimal::Decimal;
+
+/// A semantic query a user has subscribed to, rendered as a discovery feed of
+/// newly-matching repositories.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SavedQuery {
+    pub id: i32,
+    pub user_id: Decimal,
+    /// Human-readable label shown as the feed title.
+    pub label: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Timestamp of the last time the feed was rendered; matches are reported
+    /// relative to this watermark.
+    pub last_seen: chrono::DateTime<chrono::Utc>,
+}
@@ -13,6 +13,23 @@ pub struct AppConfig {
     pub github_api_url: String,
     pub github_star_threshold: u16,
     pub github_following_threshold: u16,
+    /// Minimum account age, in days, required for a caller to be eligible.
+    pub github_min_account_age_days: i64,
+    /// GitHub OAuth app client id used to drive the device-authorization flow.
+    pub github_client_id: String,
+
+    /// Session-token signing algorithm: `HS256` (HMAC) or `RS256` (RSA).
+    pub session_signing_algorithm: String,
+    /// HMAC secret used when `session_signing_algorithm` is `HS256`.
+    pub session_hmac_secret: String,
+    /// RSA private key (PEM) used when `session_signing_algorithm` is `RS256`.
+    pub session_rsa_private_key_pem: Option<String>,
+    /// RSA public key (PEM) used when `session_signing_algorithm` is `RS256`.
+    pub session_rsa_public_key_pem: Option<String>,
+    /// Lifetime of a minted access token, in seconds.
+    pub session_access_token_ttl_seconds: i64,
+    /// Lifetime of a minted refresh token, in seconds.
+    pub session_refresh_token_ttl_seconds: i64,
 
     pub api_host: String,
     pub api_port: u16,
@@ -24,6 +41,46 @@ pub struct AppConfig {
 
     pub allowed_origins: Vec<String>,
     pub log_level: String,
+    /// Log output format: `pretty` (default) or `json`
+    pub log_format: String,
+    /// When set, logs are also written to a daily-rolling file in this directory
+    pub log_dir: Option<String>,
+
+    /// Optional webhook URL notified when a job completes or fails
+    pub notifier_webhook_url: Option<String>,
+
+    /// Path to the TLS certificate chain (PEM). TLS is enabled only when both
+    /// this and `tls_key_path` are set.
+    pub tls_cert_path: Option<String>,
+    /// Path to the TLS private key (PEM)
+    pub tls_key_path: Option<String>,
+
+    /// Run pending database migrations at startup. Disable when migrations are
+    /// applied out-of-band (e.g. by a separate deploy step).
+    pub auto_migrate: bool,
+
+    /// Elapsed-time threshold, in milliseconds, above which an awaited external
+    /// operation (README fetch, embedding request, repository upsert) is logged
+    /// as slow. See [`crate::util::PollTimer`].
+    pub slow_operation_threshold_ms: u64,
+
+    /// Backend for the GitHub response cache: `none` (disabled), `filesystem`,
+    /// or `redis`. `redis` is accepted but leaves caching disabled (with a
+    /// warning), since no Redis connection is provisioned for the cache.
+    pub github_cache_backend: String,
+    /// Directory for the filesystem cache backend. One JSON file per resource is
+    /// written under per-namespace subdirectories.
+    pub github_cache_dir: String,
+    /// Default TTL, in seconds, during which a cached GitHub resource's ETag is
+    /// sent as `If-None-Match`. Past the TTL no ETag is sent and the resource
+    /// is downloaded in full.
+    pub github_cache_ttl_seconds: u64,
+
+    /// Maximum time, in seconds, the GitHub client will sleep waiting for a
+    /// primary rate-limit window to reset before surfacing an error.
+    pub github_rate_limit_max_sleep_seconds: u64,
+    /// Number of secondary rate-limit retries before giving up.
+    pub github_rate_limit_max_retries: u32,
 }
 
 impl Default for AppConfig {
@@ -38,6 +95,15 @@ impl Default for AppConfig {
             github_api_url: "https://api.github.com".to_string(),
             github_star_threshold: 500,
             github_following_threshold: 50,
+            github_min_account_age_days: 30,
+            github_client_id: String::new(),
+
+            session_signing_algorithm: "HS256".to_string(),
+            session_hmac_secret: String::new(),
+            session_rsa_private_key_pem: None,
+            session_rsa_public_key_pem: None,
+            session_access_token_ttl_seconds: 15 * 60,
+            session_refresh_token_ttl_seconds: 30 * 24 * 60 * 60,
 
             api_host: "0.0.0.0".to_string(),
             api_port: 8000,
@@ -49,6 +115,23 @@ impl Default for AppConfig {
 
             allowed_origins: vec!["http://localhost:3000".to_string()],
             log_level: "info".to_string(),
+            log_format: "pretty".to_string(),
+            log_dir: None,
+
+            notifier_webhook_url: None,
+
+            tls_cert_path: None,
+            tls_key_path: None,
+
+            auto_migrate: true,
+            slow_operation_threshold_ms: 5000,
+
+            github_cache_backend: "none".to_string(),
+            github_cache_dir: ".cache/github".to_string(),
+            github_cache_ttl_seconds: 3600,
+
+            github_rate_limit_max_sleep_seconds: 15 * 60,
+            github_rate_limit_max_retries: 5,
         }
     }
 }
@@ -57,13 +140,116 @@ impl AppConfig {
     pub fn from_env() -> Result<Self> {
         dotenvy::dotenv().ok();
 
+        // Layer a checked-in base config file (optional) underneath the
+        // environment source, so operators keep a shared config.{toml,yaml} and
+        // override only secrets via env.
         let settings = config::Config::builder()
+            .add_source(config::File::with_name("config").required(false))
             .add_source(config::Environment::default())
             .build()
             .with_context(|| "Failed to build configuration")?;
 
-        settings
+        let config: AppConfig = settings
             .try_deserialize()
-            .with_context(|| "Failed to deserialize configuration from environment")
+            .with_context(|| "Failed to deserialize configuration from environment")?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Validate the fully-resolved configuration, aggregating every problem into
+    /// a single error rather than bailing on the first so operators can fix all
+    /// of them in one pass.
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: Vec<String> = Vec::new();
+
+        // The embedding vector dimension must match the model's known width,
+        // otherwise stored vectors won't fit the schema.
+        if let Some(expected) = known_embedding_dimension(&self.ai_model_name) {
+            if self.ai_embedding_vector_dimension != expected {
+                errors.push(format!(
+                    "ai_embedding_vector_dimension {} does not match {} for model '{}'",
+                    self.ai_embedding_vector_dimension, expected, self.ai_model_name
+                ));
+            }
+        }
+
+        if self.db_port == 0 {
+            errors.push("db_port must be non-zero".to_string());
+        }
+        if self.api_port == 0 {
+            errors.push("api_port must be non-zero".to_string());
+        }
+
+        // Providers that call a hosted API need a key; an empty one is a
+        // misconfiguration we'd rather catch at startup.
+        if provider_requires_api_key(&self.ai_provider) && self.ai_api_key.trim().is_empty() {
+            errors.push(format!(
+                "ai_api_key must be non-empty for provider '{}'",
+                self.ai_provider
+            ));
+        }
+
+        if !matches!(
+            self.github_cache_backend.as_str(),
+            "none" | "filesystem" | "redis"
+        ) {
+            errors.push(format!(
+                "github_cache_backend '{}' must be one of none, filesystem, redis",
+                self.github_cache_backend
+            ));
+        }
+
+        match self.session_signing_algorithm.as_str() {
+            "HS256" => {
+                if self.session_hmac_secret.trim().is_empty() {
+                    errors.push(
+                        "session_hmac_secret must be non-empty for HS256 signing".to_string(),
+                    );
+                }
+            }
+            "RS256" => {
+                if self.session_rsa_private_key_pem.is_none()
+                    || self.session_rsa_public_key_pem.is_none()
+                {
+                    errors.push(
+                        "session_rsa_private_key_pem and session_rsa_public_key_pem are required for RS256 signing"
+                            .to_string(),
+                    );
+                }
+            }
+            other => errors.push(format!(
+                "session_signing_algorithm '{other}' must be HS256 or RS256"
+            )),
+        }
+
+        for origin in &self.allowed_origins {
+            if origin.parse::<http::Uri>().is_err() || !origin.contains("://") {
+                errors.push(format!("allowed_origins entry '{origin}' is not a valid origin"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Invalid configuration:\n  - {}",
+                errors.join("\n  - ")
+            ))
+        }
     }
 }
+
+/// Known embedding dimension for a model id, when we can vouch for it.
+fn known_embedding_dimension(model: &str) -> Option<u16> {
+    match model {
+        "text-embedding-3-small" | "text-embedding-ada-002" => Some(1536),
+        "text-embedding-3-large" => Some(3072),
+        _ => None,
+    }
+}
+
+/// Whether the configured provider authenticates with an API key.
+fn provider_requires_api_key(provider: &str) -> bool {
+    matches!(provider, "openai" | "")
+}
@@ -3,7 +3,10 @@
 use anyhow::{Context, Result};
 use sqlx::{PgPool, Row, postgres::PgPoolOptions};
 
-/// Initialize a Postgres connection pool and run migrations
+/// Initialize a Postgres connection pool.
+///
+/// Migrations are run separately via [`crate::db::Database::run_migrations`] so
+/// they can be gated behind configuration; see `init_services`.
 pub async fn init_pg_pool(database_url: &str) -> Result<PgPool> {
     // Create connection pool with reasonable settings
     let pool = PgPoolOptions::new()
@@ -13,13 +16,7 @@ pub async fn init_pg_pool(database_url: &str) -> Result<PgPool> {
         .await
         .with_context(|| format!("Failed to connect to database at {database_url}"))?;
 
-    // Run embedded migrations automatically (migrations folder is in the workspace root)
-    sqlx::migrate!("./migrations")
-        .run(&pool)
-        .await
-        .with_context(|| "Failed to run database migrations")?;
-
-    tracing::info!("Database connection pool initialized and migrations applied");
+    tracing::info!("Database connection pool initialized");
     Ok(pool)
 }
 
@@ -23,12 +23,24 @@ pub async fn job_status_handler(
 
     // Try to get the latest job for this user
     match app_state.job_manager.get_latest_job(user.id).await {
-        Ok(Some(job)) => success(json!({
-            "job": job,
-            "is_running": is_running,
-            "user_id": user.id,
-            "total_active_jobs": app_state.job_manager.active_job_count()
-        })),
+        Ok(Some(job)) => {
+            // Surface ingestion-retry health so users can see how many repositories
+            // were recovered, are still pending a retry, or gave up permanently.
+            let retry = app_state
+                .database
+                .retry_stats(job.id)
+                .await
+                .unwrap_or_default();
+            success(json!({
+                "job": job,
+                "is_running": is_running,
+                "user_id": user.id,
+                "total_active_jobs": app_state.job_manager.active_job_count(),
+                "retried": retry.retried,
+                "pending_retry": retry.pending_retry,
+                "permanently_failed": retry.permanently_failed
+            }))
+        }
         Ok(None) => success(json!({
             "job": null,
             "is_running": is_running,
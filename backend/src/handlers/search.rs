@@ -13,7 +13,7 @@ use crate::{
     app_state::AppState,
     extractors::AuthenticatedContext,
     http::{bad_request, internal_error, success},
-    services::semantic_search_manager::{SearchScope, SemanticSearchManager},
+    services::semantic_search_manager::{SearchCursor, SearchScope, SemanticSearchManager},
     types::repository::Repository,
 };
 
@@ -24,6 +24,10 @@ pub struct SearchQuery {
     pub query: String,
     /// Number of results to return (default: 10, max: 50)
     pub top_k: Option<usize>,
+    /// Page size for cursor-based paging (default: falls back to `top_k`, max: 50)
+    pub page_size: Option<usize>,
+    /// Opaque cursor returned by a previous page; resumes the scan past its boundary
+    pub cursor: Option<String>,
 }
 
 /// Response format for semantic search results
@@ -39,6 +43,8 @@ pub struct SearchResponse {
     pub query: String,
     pub results: Vec<SearchResult>,
     pub total_count: usize,
+    /// Opaque cursor for the next page, or `None` when the result set is exhausted
+    pub next_cursor: Option<String>,
 }
 
 /// Shared handler logic for semantic search
@@ -65,31 +71,41 @@ async fn handle_semantic_search(
         return bad_request("Query parameter cannot be empty");
     }
 
-    // Validate top_k parameter (default: 10, max: 50)
-    let top_k = params.top_k.unwrap_or(10);
-    if top_k == 0 || top_k > 50 {
-        return bad_request("top_k must be between 1 and 50");
+    // Validate the page size (default: 10, max: 50). `page_size` takes precedence
+    // over the legacy `top_k` alias when both are present.
+    let page_size = params.page_size.or(params.top_k).unwrap_or(10);
+    if page_size == 0 || page_size > 50 {
+        return bad_request("page_size must be between 1 and 50");
     }
 
+    // Decode the incoming cursor, if any, before touching the database.
+    let cursor = match params.cursor.as_deref() {
+        Some(raw) => match SearchCursor::decode(raw) {
+            Ok(cursor) => Some(cursor),
+            Err(_) => return bad_request("Invalid cursor"),
+        },
+        None => None,
+    };
+
     // Create RepoManager for this request
     let repo_manager = SemanticSearchManager::new(
         app_state.embedding_service.clone(),
         app_state.database.clone(),
     );
 
-    tracing::info!(query, top_k, "Performing semantic search",);
+    tracing::info!(query, page_size, "Performing semantic search",);
 
     // Perform semantic search
     match repo_manager
-        .semantic_search(query, top_k, api_key, scope)
+        .semantic_search(query, page_size, cursor, api_key, scope)
         .await
     {
-        Ok(results) => {
+        Ok((results, next_cursor)) => {
             let search_results: Vec<SearchResult> = results
                 .into_iter()
                 .map(|(repository, similarity_score)| SearchResult {
                     repository,
-                    similarity_score,
+                    similarity_score: similarity_score as f32,
                 })
                 .collect();
 
@@ -97,6 +113,7 @@ async fn handle_semantic_search(
                 query: query.to_string(),
                 total_count: search_results.len(),
                 results: search_results,
+                next_cursor: next_cursor.map(|c| c.encode()),
             };
 
             tracing::info!(
@@ -107,7 +124,8 @@ async fn handle_semantic_search(
             success(json!({
                 "query": response.query,
                 "total_count": response.total_count,
-                "results": response.results
+                "results": response.results,
+                "next_cursor": response.next_cursor
             }))
         }
         Err(e) => {
@@ -12,6 +12,7 @@ pub mod init;
 pub mod middleware;
 pub mod services;
 pub mod types;
+pub mod util;
 
 use std::sync::Arc;
 
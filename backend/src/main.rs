@@ -1,4 +1,8 @@
+use std::net::SocketAddr;
+
 use anyhow::{Context, Result};
+use axum_server::Handle;
+use axum_server::tls_rustls::RustlsConfig;
 use tokio::net::TcpListener;
 
 use starscout_backend::build_router;
@@ -7,12 +11,13 @@ use starscout_backend::init::{init_services, init_tracing};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    init_tracing()?;
-
-    // Load configuration
+    // Load configuration first so logging can be configured from it
     let config = AppConfig::from_env().context("Failed to load configuration")?;
 
+    // Initialize tracing; hold the appender guard for the process lifetime so
+    // buffered file lines flush on shutdown.
+    let _log_guard = init_tracing(&config)?;
+
     tracing::info!("Starting StarScout backend server...");
 
     // Initialize services
@@ -21,16 +26,39 @@ async fn main() -> Result<()> {
     // Build the router
     let app = build_router(app_state);
 
-    // Create TCP listener using config.api_port
-    let addr = format!("{}:{}", config.api_host, config.api_port);
-    let listener = TcpListener::bind(&addr).await?;
+    // Resolve the listen address from config
+    let addr: SocketAddr = format!("{}:{}", config.api_host, config.api_port)
+        .parse()
+        .with_context(|| "Invalid api_host/api_port")?;
+
+    // Serve over TLS when both certificate and key paths are configured,
+    // otherwise fall back to the plaintext listener.
+    match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            let tls_config = RustlsConfig::from_pem_file(cert_path, key_path)
+                .await
+                .with_context(|| "Failed to load TLS certificate and key")?;
+
+            let handle = Handle::new();
+            tokio::spawn(shutdown_signal_tls(handle.clone()));
 
-    tracing::info!("Server listening on {}", addr);
+            tracing::info!("Server listening on {} (TLS)", addr);
 
-    // Start server with graceful shutdown
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+            axum_server::bind_rustls(addr, tls_config)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        _ => {
+            let listener = TcpListener::bind(&addr).await?;
+
+            tracing::info!("Server listening on {}", addr);
+
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await?;
+        }
+    }
 
     Ok(())
 }
@@ -41,3 +69,9 @@ async fn shutdown_signal() {
         .expect("failed to install Ctrl+C handler");
     tracing::info!("Shutdown signal received, shutting down server...");
 }
+
+/// Trigger a graceful shutdown of the axum_server TLS acceptor on Ctrl+C.
+async fn shutdown_signal_tls(handle: Handle) {
+    shutdown_signal().await;
+    handle.graceful_shutdown(Some(std::time::Duration::from_secs(10)));
+}
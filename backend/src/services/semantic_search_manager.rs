@@ -1,9 +1,19 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+
 use crate::db::Database;
-use crate::embedding::{EmbeddingError, OpenAIEmbeddingService};
+use crate::embedding::{EmbeddingError, EmbeddingProvider};
 use crate::github::GitHubClient;
 use crate::types::repository::Repository;
+use crate::util::PollTimer;
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
 use sqlx::types::Decimal;
 use thiserror::Error;
+use tokio::sync::watch;
 use tracing::{debug, error, info, warn};
 
 /// Scope for semantic search
@@ -13,6 +23,33 @@ pub enum SearchScope {
     Starred { user_id: Decimal },
 }
 
+/// Opaque keyset cursor marking the last `(similarity_score, repository_id)` of a
+/// returned page. Serialized to base64 so clients can treat it as an opaque token
+/// and hand it back verbatim to fetch the next page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchCursor {
+    pub last_score: f64,
+    pub last_repo_id: Decimal,
+}
+
+impl SearchCursor {
+    /// Encode the cursor into its opaque base64 representation.
+    pub fn encode(&self) -> String {
+        // Serialization of two scalar fields cannot fail.
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        BASE64_STANDARD.encode(json)
+    }
+
+    /// Decode an opaque base64 cursor produced by [`Self::encode`].
+    pub fn decode(raw: &str) -> Result<Self, SemanticSearchManagerError> {
+        let bytes = BASE64_STANDARD
+            .decode(raw.trim())
+            .map_err(|e| SemanticSearchManagerError::ValidationError(format!("Invalid cursor: {e}")))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| SemanticSearchManagerError::ValidationError(format!("Invalid cursor: {e}")))
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum SemanticSearchManagerError {
     #[error("Database error: {0}")]
@@ -25,23 +62,61 @@ pub enum SemanticSearchManagerError {
     ConfigError(String),
     #[error("Validation error: {0}")]
     ValidationError(String),
+    /// Terminal, non-retryable error for a specific repository (e.g. malformed
+    /// metadata). Repositories failing with this are never re-queued.
+    #[error("Invalid repository: {0}")]
+    InvalidJob(String),
+}
+
+impl SemanticSearchManagerError {
+    /// Whether an error is worth retrying. Terminal, data-shaped failures are not
+    /// re-queued; transient infrastructure failures (network, rate limits, the
+    /// database) are.
+    fn is_retryable(&self) -> bool {
+        !matches!(
+            self,
+            SemanticSearchManagerError::InvalidJob(_)
+                | SemanticSearchManagerError::ValidationError(_)
+        )
+    }
+}
+
+/// Maximum number of retry attempts before a repository is marked permanently failed.
+const MAX_RETRY_ATTEMPTS: i32 = 5;
+
+/// Exponential backoff before the next retry: `2^attempt` minutes, capped at one hour.
+fn retry_backoff(attempt: i32) -> chrono::Duration {
+    let minutes = 1i64.checked_shl(attempt.min(30) as u32).unwrap_or(i64::MAX);
+    chrono::Duration::minutes(minutes.min(60))
 }
 
 #[derive(Debug, Clone)]
 pub struct SemanticSearchManager {
-    embedding_service: OpenAIEmbeddingService,
+    embedding_service: Arc<dyn EmbeddingProvider>,
     database: Database,
+    /// Operations slower than this are logged via [`PollTimer`].
+    slow_op_threshold: Duration,
 }
 
 impl SemanticSearchManager {
+    /// Default slow-operation threshold when none is configured.
+    const DEFAULT_SLOW_OP_THRESHOLD: Duration = Duration::from_secs(5);
+
     /// Create a new SemanticSearchManager instance
-    pub fn new(embedding_service: OpenAIEmbeddingService, database: Database) -> Self {
+    pub fn new(embedding_service: Arc<dyn EmbeddingProvider>, database: Database) -> Self {
         Self {
             embedding_service,
             database,
+            slow_op_threshold: Self::DEFAULT_SLOW_OP_THRESHOLD,
         }
     }
 
+    /// Set the threshold above which awaited external operations are logged as slow.
+    pub fn with_slow_operation_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_op_threshold = threshold;
+        self
+    }
+
     async fn find_repos_needing_embeddings(
         &self,
         starred_repos: Vec<Repository>,
@@ -79,12 +154,17 @@ impl SemanticSearchManager {
             .find_repos_needing_embeddings(starred_repos.clone())
             .await?;
 
-        // Process repositories in batches
-        const BATCH_SIZE: usize = 50;
+        // Fetch READMEs in coarse chunks; the embedding dispatch inside each chunk
+        // is packed by token budget rather than a fixed item count.
+        const README_FETCH_CHUNK: usize = 50;
         let mut processed_count = 0;
         let mut failed_count = 0;
         let total_repos = starred_repos.len();
 
+        // Shared rate-limit deadline honored across every embedding request in this
+        // ingestion run: a 429 on any batch backs off all subsequent requests.
+        let (rate_limit_tx, _rate_limit_rx) = watch::channel::<Option<Instant>>(None);
+
         if repos_to_process.is_empty() {
             info!(
                 "All repositories already have embeddings for user {}",
@@ -107,9 +187,9 @@ impl SemanticSearchManager {
                 repos_to_process.len()
             );
 
-            for batch in repos_to_process.chunks(BATCH_SIZE) {
+            for batch in repos_to_process.chunks(README_FETCH_CHUNK) {
                 match self
-                    .process_repository_batch(batch, user_id, api_key, github_client)
+                    .process_repository_batch(batch, user_id, api_key, github_client, &rate_limit_tx)
                     .await
                 {
                     Ok(batch_processed) => {
@@ -143,6 +223,11 @@ impl SemanticSearchManager {
                         failed_count += batch.len();
                         error!("Failed to process batch: {:?}", e);
 
+                        // Persist the failed repositories so the job manager can
+                        // re-pick them with backoff instead of dropping them until
+                        // a full re-run.
+                        self.enqueue_batch_for_retry(job_id, batch, &e).await;
+
                         // Update job progress with failures
                         let already_existing = total_repos - repos_to_process.len();
                         let total_processed = already_existing + processed_count;
@@ -169,6 +254,11 @@ impl SemanticSearchManager {
             user_id, processed_count, failed_count
         );
 
+        // Drain any repositories parked in the retry queue, honoring each one's
+        // backoff deadline, so transient failures don't require a full re-run.
+        self.drain_retry_queue(job_id, api_key, github_client, &starred_repos)
+            .await;
+
         // Update user_stars table with the list of repository IDs
         let repo_ids: Vec<Decimal> = starred_repos.iter().map(|repo| repo.id).collect();
         self.update_user_stars(user_id, &repo_ids, github_client)
@@ -184,8 +274,10 @@ impl SemanticSearchManager {
         _user_id: u64,
         api_key: &str,
         github_client: &GitHubClient,
+        rate_limit_tx: &watch::Sender<Option<Instant>>,
     ) -> Result<usize, SemanticSearchManagerError> {
         // Spawn tasks for fetching README content in parallel
+        let threshold = self.slow_op_threshold;
         let tasks: Vec<_> = repos
             .iter()
             .map(|repo| {
@@ -198,6 +290,12 @@ impl SemanticSearchManager {
                     // Try to fetch README content
                     match github_client
                         .get_readme(&enriched_repo.owner, &enriched_repo.name)
+                        .warn_if_slow(threshold, || {
+                            format!(
+                                "get_readme {}/{}",
+                                enriched_repo.owner, enriched_repo.name
+                            )
+                        })
                         .await
                     {
                         Ok(readme_content) => {
@@ -257,14 +355,30 @@ impl SemanticSearchManager {
             }
         }
 
-        // Generate embeddings for all repositories in this batch
-        let embedding_texts: Vec<String> =
-            processed_repos.iter().map(repo_to_embedding_text).collect();
-
-        let embeddings = self
-            .embedding_service
-            .get_embeddings(embedding_texts, api_key)
-            .await?;
+        // Generate embeddings, packing spans into token-budgeted sub-batches.
+        // Oversize spans are truncated to the provider's per-input cap, and spans
+        // are greedily accumulated until the next one would exceed the batch budget.
+        let max_tokens = self.embedding_service.max_tokens_per_batch();
+        let mut embeddings: Vec<Vec<f32>> = Vec::with_capacity(processed_repos.len());
+        let mut pending: Vec<String> = Vec::new();
+        let mut pending_tokens = 0usize;
+
+        for repo in &processed_repos {
+            let (text, tokens) = self.embedding_service.truncate(&repo_to_embedding_text(repo));
+            if !pending.is_empty() && pending_tokens + tokens > max_tokens {
+                let out = self
+                    .embed_batch(std::mem::take(&mut pending), api_key, rate_limit_tx)
+                    .await?;
+                embeddings.extend(out);
+                pending_tokens = 0;
+            }
+            pending.push(text);
+            pending_tokens += tokens;
+        }
+        if !pending.is_empty() {
+            let out = self.embed_batch(pending, api_key, rate_limit_tx).await?;
+            embeddings.extend(out);
+        }
 
         // Store repositories and embeddings in database
         for (repo, embedding) in processed_repos.iter().zip(embeddings.iter()) {
@@ -279,13 +393,71 @@ impl SemanticSearchManager {
         Ok(processed_repos.len())
     }
 
+    /// Dispatch a single embedding batch, honoring the shared rate-limit deadline.
+    ///
+    /// Before each attempt any pending retry-after deadline is awaited. On a
+    /// rate-limit response the deadline is extended with exponential backoff and
+    /// the same (unsent) batch is re-enqueued rather than counted as failed.
+    async fn embed_batch(
+        &self,
+        texts: Vec<String>,
+        api_key: &str,
+        rate_limit_tx: &watch::Sender<Option<Instant>>,
+    ) -> Result<Vec<Vec<f32>>, SemanticSearchManagerError> {
+        const MAX_RETRIES: u32 = 5;
+        let mut rx = rate_limit_tx.subscribe();
+
+        for attempt in 0..MAX_RETRIES {
+            // Respect any deadline set by a previous rate-limit response.
+            let deadline = *rx.borrow();
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if deadline > now {
+                    tokio::time::sleep(deadline - now).await;
+                }
+            }
+
+            let batch_len = texts.len();
+            match self
+                .embedding_service
+                .get_embeddings(texts.clone(), api_key)
+                .warn_if_slow(self.slow_op_threshold, || {
+                    format!("get_embeddings ({batch_len} spans)")
+                })
+                .await
+            {
+                Ok(embeddings) => return Ok(embeddings),
+                Err(e) if is_rate_limit(&e) => {
+                    let backoff = Duration::from_secs(1 << attempt.min(6));
+                    warn!(
+                        "Embedding request rate-limited, backing off {:?} (attempt {}/{})",
+                        backoff,
+                        attempt + 1,
+                        MAX_RETRIES
+                    );
+                    let _ = rate_limit_tx.send(Some(Instant::now() + backoff));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Err(SemanticSearchManagerError::EmbeddingError(
+            EmbeddingError::ValidationError("Embedding rate-limit retries exhausted".to_string()),
+        ))
+    }
+
     /// Store a repository with its embedding in the database
     async fn store_repository_with_embedding(
         &self,
         repo: &Repository,
         embedding: &[f32],
     ) -> Result<(), SemanticSearchManagerError> {
-        self.database.upsert_repository(repo, embedding).await?;
+        self.database
+            .upsert_repository(repo, embedding)
+            .warn_if_slow(self.slow_op_threshold, || {
+                format!("upsert_repository {}/{}", repo.owner, repo.name)
+            })
+            .await?;
 
         debug!(
             "Stored repository {}/{} with embedding",
@@ -294,6 +466,150 @@ impl SemanticSearchManager {
         Ok(())
     }
 
+    /// Persist a failed batch to the retry queue. Transient failures are queued
+    /// with a first-attempt backoff; non-retryable errors are recorded as
+    /// permanently failed so they are never re-picked.
+    async fn enqueue_batch_for_retry(
+        &self,
+        job_id: i32,
+        repos: &[Repository],
+        err: &SemanticSearchManagerError,
+    ) {
+        let retryable = err.is_retryable();
+        let now = Utc::now();
+        for repo in repos {
+            let result = if retryable {
+                self.database
+                    .enqueue_repo_retry(job_id, repo.id, 1, now + retry_backoff(1))
+                    .await
+            } else {
+                self.database
+                    .mark_repo_permanently_failed(job_id, repo.id)
+                    .await
+            };
+            if let Err(e) = result {
+                warn!(
+                    "Failed to record retry for {}/{}: {}",
+                    repo.owner, repo.name, e
+                );
+            }
+        }
+    }
+
+    /// Re-process any repositories whose retry is due, advancing each one's backoff
+    /// on repeated failure and marking it permanently failed once it exhausts
+    /// [`MAX_RETRY_ATTEMPTS`] or hits a non-retryable error.
+    ///
+    /// Returns `(recovered, permanently_failed)` counts for this pass.
+    pub async fn retry_pending_repos(
+        &self,
+        job_id: i32,
+        api_key: &str,
+        github_client: &GitHubClient,
+        all_repos: &[Repository],
+    ) -> Result<(usize, usize), SemanticSearchManagerError> {
+        let now = Utc::now();
+        let due: HashMap<Decimal, i32> =
+            self.database.due_repo_retries(job_id, now).await?.into_iter().collect();
+        if due.is_empty() {
+            return Ok((0, 0));
+        }
+
+        let (rate_limit_tx, _rate_limit_rx) = watch::channel::<Option<Instant>>(None);
+        let mut recovered = 0;
+        let mut permanently_failed = 0;
+
+        // Retry each repository on its own so a single malformed entry cannot
+        // poison the rest of the due set.
+        for repo in all_repos.iter().filter(|r| due.contains_key(&r.id)) {
+            let attempt = due.get(&repo.id).copied().unwrap_or(0);
+            match self
+                .process_repository_batch(
+                    std::slice::from_ref(repo),
+                    0,
+                    api_key,
+                    github_client,
+                    &rate_limit_tx,
+                )
+                .await
+            {
+                Ok(_) => {
+                    if let Err(e) = self.database.clear_repo_retry(job_id, repo.id).await {
+                        warn!("Failed to clear retry for {}/{}: {}", repo.owner, repo.name, e);
+                    }
+                    recovered += 1;
+                }
+                Err(e) if e.is_retryable() && attempt + 1 < MAX_RETRY_ATTEMPTS => {
+                    let next = now + retry_backoff(attempt + 1);
+                    if let Err(e) =
+                        self.database.enqueue_repo_retry(job_id, repo.id, attempt + 1, next).await
+                    {
+                        warn!("Failed to re-queue {}/{}: {}", repo.owner, repo.name, e);
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "Giving up on {}/{} after {} attempts: {:?}",
+                        repo.owner, repo.name, attempt, e
+                    );
+                    if let Err(e) = self.database.mark_repo_permanently_failed(job_id, repo.id).await
+                    {
+                        warn!(
+                            "Failed to mark {}/{} permanently failed: {}",
+                            repo.owner, repo.name, e
+                        );
+                    }
+                    permanently_failed += 1;
+                }
+            }
+        }
+
+        Ok((recovered, permanently_failed))
+    }
+
+    /// Repeatedly re-pick due retries for a job until none remain pending,
+    /// sleeping between passes so backed-off entries come due.
+    ///
+    /// Each entry falls out of the queue once it succeeds or exhausts
+    /// [`MAX_RETRY_ATTEMPTS`], so this terminates; failures are logged rather
+    /// than propagated because the main pass already reported the job's outcome.
+    async fn drain_retry_queue(
+        &self,
+        job_id: i32,
+        api_key: &str,
+        github_client: &GitHubClient,
+        all_repos: &[Repository],
+    ) {
+        loop {
+            match self.database.retry_stats(job_id).await {
+                Ok(stats) if stats.pending_retry == 0 => return,
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Failed to read retry stats for job {}: {}", job_id, e);
+                    return;
+                }
+            }
+
+            match self
+                .retry_pending_repos(job_id, api_key, github_client, all_repos)
+                .await
+            {
+                Ok((recovered, permanently_failed)) => {
+                    // No entry was resolved this pass (none were due, or all were
+                    // re-queued); wait out the shortest backoff before polling again so we don't spin on not-yet-due entries.
+                    if recovered == 0 && permanently_failed == 0 {
+                        let wait = retry_backoff(1).to_std().unwrap_or(Duration::from_secs(60));
+                        tokio::time::sleep(wait).await;
+                    }
+                }
+                Err(e) => {
+                    warn!("Retry pass failed for job {}: {}", job_id, e);
+                    return;
+                }
+            }
+        }
+    }
+
     /// Update the user_stars table with the list of starred repository IDs
     async fn update_user_stars(
         &self,
@@ -319,62 +635,72 @@ impl SemanticSearchManager {
         Ok(())
     }
 
-    /// Perform semantic search on repositories (global or starred)
+    /// Perform a page of semantic search on repositories (global or starred).
+    ///
+    /// Returns the page of results plus a `next_cursor` that resumes the scan past
+    /// the last `(similarity_score, repository_id)` boundary, or `None` when the
+    /// page is exhausted.
     pub async fn semantic_search(
         &self,
         query: &str,
-        top_k: usize,
+        page_size: usize,
+        cursor: Option<SearchCursor>,
         api_key: &str,
         scope: SearchScope,
-    ) -> Result<Vec<(Repository, f32)>, SemanticSearchManagerError> {
+    ) -> Result<(Vec<(Repository, f64)>, Option<SearchCursor>), SemanticSearchManagerError> {
         if query.is_empty() {
             return Err(SemanticSearchManagerError::ValidationError(
                 "Query cannot be empty".to_string(),
             ));
         }
 
-        if top_k == 0 {
-            return Ok(Vec::new());
+        if page_size == 0 {
+            return Ok((Vec::new(), None));
         }
 
-        match scope {
+        let after = cursor.map(|c| (c.last_score, c.last_repo_id));
+
+        let results = match scope {
             SearchScope::Global => {
-                debug!(
-                    "Performing semantic search for query: '{}', top_k: {}",
-                    query, top_k
-                );
-                // Generate embedding for the query
+                debug!(query, page_size, "Performing semantic search",);
                 let query_embedding = self.embedding_service.get_embedding(query, api_key).await?;
-                // Use Database method for semantic search
-                let results = self
-                    .database
-                    .semantic_search_repositories(&query_embedding, top_k)
-                    .await?;
-                info!("Found {} results for semantic search query", results.len());
-                Ok(results)
+                self.database
+                    .semantic_search_repositories_page(&query_embedding, page_size, after)
+                    .await?
             }
             SearchScope::Starred { user_id } => {
-                debug!(query, top_k, "Performing semantic search on starred repos",);
-                // Get user's starred repository IDs to check if user has stars
+                debug!(query, page_size, "Performing semantic search on starred repos",);
+                // Short-circuit when the user has no stars stored yet.
                 let starred_repo_ids = self.get_user_starred_repo_ids_by_string(user_id).await?;
                 if starred_repo_ids.is_empty() {
                     debug!("User has no starred repositories",);
-                    return Ok(Vec::new());
+                    return Ok((Vec::new(), None));
                 }
-                // Generate embedding for the query
                 let query_embedding = self.embedding_service.get_embedding(query, api_key).await?;
-                // Use Database method for semantic search on starred repositories
-                let results = self
-                    .database
-                    .semantic_search_starred_repositories(&query_embedding, user_id, top_k)
-                    .await?;
-                info!(
-                    "Found {} results for starred repositories search",
-                    results.len(),
-                );
-                Ok(results)
+                self.database
+                    .semantic_search_starred_repositories_page(
+                        &query_embedding,
+                        user_id,
+                        page_size,
+                        after,
+                    )
+                    .await?
             }
-        }
+        };
+
+        info!("Found {} results for semantic search query", results.len());
+
+        // Only emit a cursor when the page came back full; a short page is the last one.
+        let next_cursor = if results.len() == page_size {
+            results.last().map(|(repo, score)| SearchCursor {
+                last_score: *score,
+                last_repo_id: repo.id,
+            })
+        } else {
+            None
+        };
+
+        Ok((results, next_cursor))
     }
 
     /// Get repository count for statistics
@@ -408,6 +734,20 @@ impl SemanticSearchManager {
     }
 }
 
+/// Best-effort detection of a rate-limit (HTTP 429) embedding error.
+///
+/// `async-openai` does not surface the response headers, so the `Retry-After`
+/// value cannot be read directly; callers fall back to exponential backoff.
+fn is_rate_limit(error: &EmbeddingError) -> bool {
+    match error {
+        EmbeddingError::ApiError(e) => {
+            let msg = e.to_string().to_lowercase();
+            msg.contains("429") || msg.contains("rate limit") || msg.contains("rate_limit")
+        }
+        _ => false,
+    }
+}
+
 fn repo_to_embedding_text(repo: &Repository) -> String {
     let repo_name = format!("{}/{}", repo.owner, repo.name);
     let description = repo.description.as_deref().unwrap_or("None");
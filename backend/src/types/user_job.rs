@@ -3,6 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, types::Decimal};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UserJob {
@@ -10,7 +11,7 @@ pub struct UserJob {
     pub id: i32,
     /// GitHub user ID
     pub user_id: Decimal,
-    /// Job status (pending, processing, completed, failed)
+    /// Job status (pending, queued, running, completed, failed)
     pub status: String,
     /// Total number of repositories to process
     pub total_repos: i32,
@@ -18,6 +19,10 @@ pub struct UserJob {
     pub processed_repos: i32,
     /// Number of repositories that failed processing
     pub failed_repos: i32,
+    /// Worker currently holding this job, if claimed
+    pub worker_id: Option<Uuid>,
+    /// Last heartbeat from the claiming worker; used to reap crashed workers
+    pub heartbeat: Option<DateTime<Utc>>,
     /// Job creation timestamp
     pub created_at: DateTime<Utc>,
     /// Job last update timestamp
@@ -0,0 +1,3 @@
+pub mod poll_timer;
+
+pub use poll_timer::PollTimer;
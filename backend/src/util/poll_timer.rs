@@ -0,0 +1,39 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+/// Extension trait that wraps a future to warn when it takes longer than a
+/// threshold to resolve.
+///
+/// Large ingestion runs can stall on a single slow external call with no
+/// visibility short of full debug logging. Wrapping the await with
+/// [`warn_if_slow`](PollTimer::warn_if_slow) emits a single `WARN` line carrying
+/// the elapsed time and a caller-supplied label (typically the repo identity) so
+/// operators can pinpoint the bottleneck dependency.
+pub trait PollTimer: Future + Sized {
+    /// Await `self`, logging a warning if it takes at least `threshold`.
+    ///
+    /// `label` describes the operation (e.g. `"get_readme rust-lang/rust"`); it is
+    /// evaluated only when the warning fires.
+    async fn warn_if_slow<F>(self, threshold: Duration, label: F) -> Self::Output
+    where
+        F: FnOnce() -> String,
+    {
+        let started = Instant::now();
+        let output = self.await;
+        let elapsed = started.elapsed();
+        if elapsed >= threshold {
+            warn!(
+                elapsed_ms = elapsed.as_millis() as u64,
+                threshold_ms = threshold.as_millis() as u64,
+                "Slow operation: {} took {:?}",
+                label(),
+                elapsed
+            );
+        }
+        output
+    }
+}
+
+impl<T: Future + Sized> PollTimer for T {}